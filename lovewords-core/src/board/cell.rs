@@ -3,7 +3,7 @@
 //! A cell represents a single button position on a board, with associated
 //! actions and metadata.
 
-use crate::obf::{ObfButton, ObfExtensions};
+use crate::obf::{ObfButton, ObfExtensions, ObfLoadBoard};
 
 /// A cell on a communication board.
 ///
@@ -87,49 +87,82 @@ impl<'a> Cell<'a> {
         &self.button.extensions
     }
 
-    /// Get the action this cell will perform when activated.
+    /// Get the primary action this cell will perform when activated.
+    ///
+    /// Buttons can perform several actions; see [`Cell::actions`].
     pub fn action(&self) -> CellAction {
         // Check for explicit action
-        if let Some(action) = &self.button.action {
-            match action.as_str() {
-                ":speak" => return CellAction::Speak(self.speak_text().to_string()),
-                ":back" => return CellAction::Back,
-                ":clear" => return CellAction::Clear,
-                ":home" => return CellAction::Home,
-                ":backspace" => return CellAction::Backspace,
-                ":space" => return CellAction::AddWord(" ".to_string()),
-                other => return CellAction::Custom(other.to_string()),
-            }
+        if let Some(action) = self.explicit_actions().next() {
+            return action;
         }
 
         // Check for navigation
-        if let Some(load_board) = &self.button.load_board {
-            if let Some(board_id) = &load_board.id {
-                return CellAction::Navigate(board_id.clone());
-            }
-            if let Some(path) = &load_board.path {
-                return CellAction::NavigatePath(path.clone());
-            }
+        if let Some(action) = self.navigation() {
+            return action;
         }
 
         // Default: speak the label/vocalization
         CellAction::Speak(self.speak_text().to_string())
     }
 
+    /// Get every action this cell performs when activated, in order.
+    ///
+    /// A recorded sound plays first, then the explicit `actions` (or the
+    /// single `action`), then navigation via `load_board`. Buttons with
+    /// neither an explicit action nor a board link speak their label, as in
+    /// [`Cell::action`].
+    pub fn actions(&self) -> Vec<CellAction> {
+        let mut actions = Vec::new();
+
+        if let Some(sound_id) = self.sound_id() {
+            actions.push(CellAction::PlaySound(sound_id.to_string()));
+        }
+        let sound = actions.len();
+        actions.extend(self.explicit_actions());
+        actions.extend(self.navigation());
+
+        if actions.len() == sound {
+            actions.push(CellAction::Speak(self.speak_text().to_string()));
+        }
+        actions
+    }
+
     /// Check if this cell will speak when activated.
     pub fn is_speakable(&self) -> bool {
-        matches!(self.action(), CellAction::Speak(_))
+        self.actions()
+            .iter()
+            .any(|a| matches!(a, CellAction::Speak(_)))
     }
 
     /// Check if this cell navigates to another board.
     pub fn is_navigation(&self) -> bool {
-        matches!(
-            self.action(),
-            CellAction::Navigate(_)
-                | CellAction::NavigatePath(_)
-                | CellAction::Back
-                | CellAction::Home
-        )
+        self.actions().iter().any(CellAction::is_navigate)
+    }
+
+    fn explicit_actions(&self) -> impl Iterator<Item = CellAction> + '_ {
+        let actions: &[String] = if self.button.actions.is_empty() {
+            self.button.action.as_slice()
+        } else {
+            &self.button.actions
+        };
+        actions
+            .iter()
+            .map(|action| CellAction::from_obf(action, self.speak_text()))
+    }
+
+    fn navigation(&self) -> Option<CellAction> {
+        let load_board = self.button.load_board.as_ref()?;
+        if let Some(board_id) = &load_board.id {
+            return Some(CellAction::Navigate(board_id.clone()));
+        }
+        if let Some(path) = &load_board.path {
+            return Some(CellAction::NavigatePath(path.clone()));
+        }
+        load_board
+            .url
+            .as_ref()
+            .or(load_board.data_url.as_ref())
+            .map(|url| CellAction::NavigateUrl(url.clone()))
     }
 }
 
@@ -145,6 +178,9 @@ pub enum CellAction {
     /// Navigate to another board by file path.
     NavigatePath(String),
 
+    /// Navigate to another board by URL, including inline `data:` URLs.
+    NavigateUrl(String),
+
     /// Go back to the previous board.
     Back,
 
@@ -154,12 +190,22 @@ pub enum CellAction {
     /// Clear the message bar.
     Clear,
 
-    /// Remove the last word from the message bar.
+    /// Remove the last word from the message bar, or the last letter
+    /// while a word is being spelled.
     Backspace,
 
     /// Add a word to the message bar without speaking.
     AddWord(String),
 
+    /// Append text to the word being spelled (OBF `+text` actions).
+    AppendText(String),
+
+    /// Finish the word being spelled.
+    Space,
+
+    /// Vendor-specific action from an `:ext_<namespace>_<name>` action string.
+    Extension { namespace: String, name: String },
+
     /// Play a sound.
     PlaySound(String),
 
@@ -168,6 +214,34 @@ pub enum CellAction {
 }
 
 impl CellAction {
+    /// Parse an OBF action string.
+    ///
+    /// `speak_text` is used for `:speak`. Unknown actions become
+    /// [`CellAction::Custom`].
+    pub fn from_obf(action: &str, speak_text: &str) -> Self {
+        if let Some(text) = action.strip_prefix('+') {
+            return CellAction::AppendText(text.to_string());
+        }
+        if let Some(rest) = action.strip_prefix(":ext_") {
+            if let Some((namespace, name)) = rest.split_once('_') {
+                return CellAction::Extension {
+                    namespace: namespace.to_string(),
+                    name: name.to_string(),
+                };
+            }
+        }
+
+        match action {
+            ":speak" => CellAction::Speak(speak_text.to_string()),
+            ":back" => CellAction::Back,
+            ":clear" => CellAction::Clear,
+            ":home" => CellAction::Home,
+            ":backspace" => CellAction::Backspace,
+            ":space" => CellAction::Space,
+            other => CellAction::Custom(other.to_string()),
+        }
+    }
+
     /// Check if this action speaks text.
     pub fn is_speak(&self) -> bool {
         matches!(self, CellAction::Speak(_))
@@ -179,6 +253,7 @@ impl CellAction {
             self,
             CellAction::Navigate(_)
                 | CellAction::NavigatePath(_)
+                | CellAction::NavigateUrl(_)
                 | CellAction::Back
                 | CellAction::Home
         )
@@ -199,6 +274,22 @@ impl CellAction {
             _ => None,
         }
     }
+
+    /// Get the board link for a navigate action, for use with a
+    /// [`BoardResolver`](super::BoardResolver).
+    pub fn load_board(&self) -> Option<ObfLoadBoard> {
+        let mut link = ObfLoadBoard::default();
+        match self {
+            CellAction::Navigate(id) => link.id = Some(id.clone()),
+            CellAction::NavigatePath(path) => link.path = Some(path.clone()),
+            CellAction::NavigateUrl(url) if url.starts_with("data:") => {
+                link.data_url = Some(url.clone())
+            }
+            CellAction::NavigateUrl(url) => link.url = Some(url.clone()),
+            _ => return None,
+        }
+        Some(link)
+    }
 }
 
 #[cfg(test)]
@@ -234,6 +325,131 @@ mod tests {
         assert!(cell.is_navigation());
     }
 
+    #[test]
+    fn test_cell_action_navigate_url() {
+        let mut button = ObfButton::new("remote", "Remote");
+        button.load_board = Some(ObfLoadBoard {
+            url: Some("https://boards.example/a.obf".to_string()),
+            ..Default::default()
+        });
+        let cell = Cell::new(&button, 0, 0);
+
+        let action = cell.action();
+        assert_eq!(
+            action,
+            CellAction::NavigateUrl("https://boards.example/a.obf".to_string())
+        );
+        assert!(cell.is_navigation());
+        assert_eq!(
+            action.load_board().unwrap().url.as_deref(),
+            Some("https://boards.example/a.obf")
+        );
+
+        let inline = CellAction::NavigateUrl("data:application/json,{}".to_string());
+        assert!(inline.load_board().unwrap().data_url.is_some());
+    }
+
+    #[test]
+    fn test_spelling_actions() {
+        assert_eq!(
+            CellAction::from_obf("+a", ""),
+            CellAction::AppendText("a".to_string())
+        );
+        assert_eq!(
+            CellAction::from_obf("+ing", ""),
+            CellAction::AppendText("ing".to_string())
+        );
+        assert_eq!(CellAction::from_obf(":space", ""), CellAction::Space);
+        assert_eq!(
+            CellAction::from_obf(":backspace", ""),
+            CellAction::Backspace
+        );
+        assert_eq!(
+            CellAction::from_obf(":ext_coughdrop_speak_now", ""),
+            CellAction::Extension {
+                namespace: "coughdrop".to_string(),
+                name: "speak_now".to_string()
+            }
+        );
+        assert_eq!(
+            CellAction::from_obf(":unknown", ""),
+            CellAction::Custom(":unknown".to_string())
+        );
+
+        let mut key = ObfButton::new("key_b", "b");
+        key.action = Some("+b".to_string());
+        let cell = Cell::new(&key, 0, 0);
+        assert_eq!(cell.action(), CellAction::AppendText("b".to_string()));
+        assert!(!cell.is_speakable());
+    }
+
+    #[test]
+    fn test_sound_only_button() {
+        // The recording plays, and the label is still spoken
+        let mut button = ObfButton::new("laugh", "Laugh");
+        button.sound_id = Some("snd_laugh".to_string());
+        let cell = Cell::new(&button, 0, 0);
+        let speak = CellAction::Speak("Laugh".to_string());
+        assert_eq!(
+            cell.actions(),
+            vec![
+                CellAction::PlaySound("snd_laugh".to_string()),
+                speak.clone()
+            ]
+        );
+        assert_eq!(cell.action(), speak);
+        assert!(cell.is_speakable());
+        assert!(!cell.is_navigation());
+    }
+
+    #[test]
+    fn test_multiple_actions() {
+        // Speak, then navigate
+        let mut hungry = ObfButton::navigate("hungry", "I'm hungry", "food");
+        hungry.action = Some(":speak".to_string());
+        let cell = Cell::new(&hungry, 0, 0);
+        assert_eq!(
+            cell.actions(),
+            vec![
+                CellAction::Speak("I'm hungry".to_string()),
+                CellAction::Navigate("food".to_string()),
+            ]
+        );
+        assert!(cell.is_speakable());
+        assert!(cell.is_navigation());
+
+        // Recorded sound, then home
+        let mut laugh = ObfButton::new("laugh", "Laugh").with_action(":home");
+        laugh.sound_id = Some("snd_laugh".to_string());
+        let cell = Cell::new(&laugh, 0, 0);
+        assert_eq!(
+            cell.actions(),
+            vec![
+                CellAction::PlaySound("snd_laugh".to_string()),
+                CellAction::Home,
+            ]
+        );
+        assert_eq!(cell.action(), CellAction::Home);
+
+        // The actions array takes precedence over action
+        let mut spelled = ObfButton::new("ing", "ing")
+            .with_action("+ing")
+            .with_action(":space");
+        spelled.action = Some(":clear".to_string());
+        let cell = Cell::new(&spelled, 0, 0);
+        assert_eq!(
+            cell.actions(),
+            vec![CellAction::AppendText("ing".to_string()), CellAction::Space]
+        );
+
+        // Plain buttons still speak their label
+        let plain = ObfButton::new("hi", "Hi");
+        assert_eq!(
+            Cell::new(&plain, 0, 0).actions(),
+            vec![CellAction::Speak("Hi".to_string())]
+        );
+    }
+
     #[test]
     fn test_cell_vocalization() {
         let button = ObfButton::new("btn_1", "Hi").with_vocalization("Hello there!");
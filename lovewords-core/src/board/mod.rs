@@ -5,9 +5,14 @@
 
 mod cell;
 mod navigation;
+mod resolver;
 
 pub use cell::{Cell, CellAction};
 pub use navigation::BoardNavigator;
+pub use resolver::{
+    BoardFetcher, BoardResolver, DataUrlResolver, PathResolver, ResolverChain, StorageResolver,
+    UrlResolver,
+};
 
 use crate::error::{BoardError, Result};
 use crate::obf::{ObfBoard, ObfButton, ObfExtensions};
@@ -93,6 +98,11 @@ impl Board {
         self.obf.grid.cell_count()
     }
 
+    /// Get a `[row][col]` mask of positions holding a visible button.
+    pub fn occupancy(&self) -> Vec<Vec<bool>> {
+        self.obf.occupancy()
+    }
+
     /// Get the LoveWords extensions for this board.
     pub fn extensions(&self) -> &ObfExtensions {
         &self.obf.extensions
@@ -3,8 +3,9 @@
 //! The [`BoardNavigator`] manages the navigation stack and current position
 //! as users interact with nested boards.
 
+use super::resolver::{self, BoardResolver};
 use crate::error::{BoardError, Result};
-use crate::obf::ObfBoard;
+use crate::obf::{ObfBoard, ObfLoadBoard};
 
 /// Manages navigation state across a hierarchy of boards.
 ///
@@ -38,6 +39,8 @@ pub struct BoardNavigator {
     cursor: Option<(usize, usize)>,
     /// ID of the home board for :home action.
     home_id: String,
+    /// Whether links to a board further down the stack unwind to it.
+    unwind: bool,
 }
 
 impl BoardNavigator {
@@ -49,9 +52,21 @@ impl BoardNavigator {
             current: home,
             cursor: None,
             home_id,
+            unwind: false,
         }
     }
 
+    /// Set whether following a link to a board already on the stack
+    /// unwinds back to it instead of pushing a second copy.
+    ///
+    /// Off by default, so Back retraces every board visited. Turn it on
+    /// to keep boards that link to each other from growing the stack
+    /// without bound.
+    pub fn with_unwinding(mut self, unwind: bool) -> Self {
+        self.unwind = unwind;
+        self
+    }
+
     /// Get the current board.
     pub fn current(&self) -> &ObfBoard {
         &self.current
@@ -84,6 +99,48 @@ impl BoardNavigator {
         self.cursor = None;
     }
 
+    /// Follow a board link, resolving it with `resolver`.
+    ///
+    /// If the target is already the current board nothing changes. If it is
+    /// further down the stack, the navigator unwinds back to it when
+    /// [unwinding](Self::with_unwinding) is on, and pushes it otherwise.
+    pub fn navigate(&mut self, link: &ObfLoadBoard, resolver: &dyn BoardResolver) -> Result<()> {
+        if let Some(id) = &link.id {
+            if self.unwind_to(id) {
+                return Ok(());
+            }
+        }
+
+        let board = resolver
+            .resolve(link)?
+            .ok_or_else(|| BoardError::UnresolvedLink(resolver::describe(link)))?;
+        if !self.unwind_to(&board.id) {
+            self.push(board);
+        }
+        Ok(())
+    }
+
+    /// Make the board with `id` current if it is already open.
+    ///
+    /// Returns `false` if the board isn't current and can't be unwound to.
+    fn unwind_to(&mut self, id: &str) -> bool {
+        if self.current.id == id {
+            return true;
+        }
+        if !self.unwind {
+            return false;
+        }
+        match self.stack.iter().rposition(|b| b.id == id) {
+            Some(index) => {
+                self.stack.truncate(index + 1);
+                self.current = self.stack.pop().expect("index is within the stack");
+                self.cursor = None;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Go back to the previous board.
     ///
     /// Returns the board we're leaving, or an error if at root.
@@ -353,6 +410,74 @@ mod tests {
         assert_eq!(nav.breadcrumbs(), vec!["Home", "Emotions", "Happy"]);
     }
 
+    #[test]
+    fn test_navigate_with_resolver() {
+        use crate::board::{ResolverChain, StorageResolver};
+        use crate::storage::MemoryStorage;
+        use std::sync::Arc;
+
+        let storage = Arc::new(MemoryStorage::with_boards(vec![
+            make_board("a", 2, 2),
+            make_board("b", 2, 2),
+        ]));
+        let resolver = ResolverChain::new().with(StorageResolver::new(storage));
+        let link = |id: &str| ObfLoadBoard {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+
+        let mut nav = BoardNavigator::new(make_board("home", 2, 2));
+        nav.navigate(&link("a"), &resolver).unwrap();
+        nav.navigate(&link("b"), &resolver).unwrap();
+        assert_eq!(nav.current().id, "b");
+        assert_eq!(nav.depth(), 2);
+
+        // Linking back to a board already open pushes it, so Back
+        // retraces every board visited
+        nav.navigate(&link("a"), &resolver).unwrap();
+        assert_eq!(nav.depth(), 3);
+        assert_eq!(nav.pop().unwrap().id, "a");
+        assert_eq!(nav.current().id, "b");
+
+        // Linking to the current board does nothing
+        nav.navigate(&link("b"), &resolver).unwrap();
+        assert_eq!(nav.depth(), 2);
+
+        assert!(nav.navigate(&link("missing"), &resolver).is_err());
+    }
+
+    #[test]
+    fn test_navigate_with_unwinding() {
+        use crate::board::{ResolverChain, StorageResolver};
+        use crate::storage::MemoryStorage;
+        use std::sync::Arc;
+
+        let storage = Arc::new(MemoryStorage::with_boards(vec![
+            make_board("a", 2, 2),
+            make_board("b", 2, 2),
+        ]));
+        let resolver = ResolverChain::new().with(StorageResolver::new(storage));
+        let link = |id: &str| ObfLoadBoard {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+
+        let mut nav = BoardNavigator::new(make_board("home", 2, 2)).with_unwinding(true);
+        nav.navigate(&link("a"), &resolver).unwrap();
+        nav.navigate(&link("b"), &resolver).unwrap();
+
+        // Linking back to a board already open unwinds instead of pushing
+        nav.navigate(&link("a"), &resolver).unwrap();
+        assert_eq!(nav.current().id, "a");
+        assert_eq!(nav.depth(), 1);
+        assert_eq!(nav.pop().unwrap().id, "a");
+        assert!(nav.is_at_home());
+
+        nav.navigate(&link("a"), &resolver).unwrap();
+        nav.navigate(&link("home"), &resolver).unwrap();
+        assert!(nav.is_at_home());
+    }
+
     #[test]
     fn test_pop_at_root_fails() {
         let home = make_board("home", 2, 3);
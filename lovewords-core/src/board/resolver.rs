@@ -0,0 +1,582 @@
+//! Resolving board links to boards.
+//!
+//! A button's `load_board` can point at another board by `id`, `path`, `url`
+//! or inline `data_url`. A [`BoardResolver`] turns such a link into an
+//! [`ObfBoard`]; [`ResolverChain`] combines several resolvers, caches what
+//! they return, and can walk a whole board set without looping forever on
+//! boards that link to each other.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use lovewords_core::board::{ResolverChain, StorageResolver};
+//! use lovewords_core::{MemoryStorage, ObfBoard, ObfLoadBoard};
+//!
+//! let storage = Arc::new(MemoryStorage::with_boards(vec![ObfBoard::new("feelings", 2, 2)]));
+//! let resolver = ResolverChain::new().with(StorageResolver::new(storage));
+//!
+//! let link = ObfLoadBoard { id: Some("feelings".to_string()), ..Default::default() };
+//! let board = resolver.resolve_link(&link).unwrap();
+//! assert_eq!(board.id, "feelings");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::error::{BoardError, LoveWordsError, ObfError, Result, StorageError};
+use crate::obf::{ObfBoard, ObfLoadBoard};
+use crate::storage::{BoardId, StorageBackend};
+
+/// Turns a board link into a board.
+pub trait BoardResolver: Send + Sync {
+    /// Resolve a link.
+    ///
+    /// Returns `Ok(None)` if this resolver doesn't handle the kind of link
+    /// given, so that another resolver can try.
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>>;
+}
+
+/// Resolves `id` links from a [`StorageBackend`].
+pub struct StorageResolver {
+    storage: Arc<dyn StorageBackend>,
+}
+
+impl StorageResolver {
+    /// Create a resolver backed by the given storage.
+    pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
+        Self { storage }
+    }
+}
+
+impl BoardResolver for StorageResolver {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        let Some(id) = &link.id else {
+            return Ok(None);
+        };
+        match self.storage.load_board(&BoardId::new(id.as_str())) {
+            Ok(board) => Ok(Some(board)),
+            Err(StorageError::BoardNotFound(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Resolves `path` links relative to a base directory.
+///
+/// Paths that could leave the base directory, with `..`, a root or a drive
+/// prefix, are rejected as unresolved.
+#[derive(Debug, Clone)]
+pub struct PathResolver {
+    base_dir: PathBuf,
+}
+
+impl PathResolver {
+    /// Create a resolver for paths relative to `base_dir`.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    /// Get the base directory.
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+}
+
+impl BoardResolver for PathResolver {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        let Some(path) = &link.path else {
+            return Ok(None);
+        };
+        let relative = Path::new(path.trim_start_matches('/'));
+        let escapes = relative.components().any(|component| {
+            matches!(
+                component,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if escapes {
+            return Err(BoardError::UnresolvedLink(describe(link)).into());
+        }
+        let full_path = self.base_dir.join(relative);
+        let json = match std::fs::read(&full_path) {
+            Ok(json) => json,
+            // Let another resolver try, e.g. by url
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(StorageError::from(err).into()),
+        };
+        let board = serde_json::from_slice(&json).map_err(ObfError::from)?;
+        Ok(Some(board))
+    }
+}
+
+/// Resolves inline `data:` URLs containing board JSON.
+///
+/// Both base64 (`data:application/json;base64,...`) and percent-encoded
+/// (`data:application/json,...`) payloads are supported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataUrlResolver;
+
+impl BoardResolver for DataUrlResolver {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        let Some(data_url) = &link.data_url else {
+            return Ok(None);
+        };
+        let json = decode_data_url(data_url)?;
+        let board = serde_json::from_slice(&json).map_err(ObfError::from)?;
+        Ok(Some(board))
+    }
+}
+
+/// Fetches the contents of a board URL.
+///
+/// The core never touches the network itself; clients supply a fetcher backed
+/// by their platform's HTTP stack. Any `Fn(&str) -> Result<Vec<u8>>` closure
+/// works as a fetcher, which makes local stand-ins easy to write in tests.
+pub trait BoardFetcher: Send + Sync {
+    /// Fetch the raw bytes at `url`.
+    fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+impl<F> BoardFetcher for F
+where
+    F: Fn(&str) -> Result<Vec<u8>> + Send + Sync,
+{
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        self(url)
+    }
+}
+
+/// Resolves `url` links through a [`BoardFetcher`].
+pub struct UrlResolver<F: BoardFetcher> {
+    fetcher: F,
+}
+
+impl<F: BoardFetcher> UrlResolver<F> {
+    /// Create a resolver that fetches URLs with `fetcher`.
+    pub fn new(fetcher: F) -> Self {
+        Self { fetcher }
+    }
+}
+
+impl<F: BoardFetcher> BoardResolver for UrlResolver<F> {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        let Some(url) = &link.url else {
+            return Ok(None);
+        };
+        let json = self.fetcher.fetch(url)?;
+        let board = serde_json::from_slice(&json).map_err(ObfError::from)?;
+        Ok(Some(board))
+    }
+}
+
+/// Tries several resolvers in order and caches the boards they return.
+///
+/// Resolved boards are cached under the link that produced them and under
+/// their own ID, so a board first reached by path is found again when
+/// another button links to it by ID. Links with an inline data URL are not
+/// cached, so an inline board can't replace a stored one with the same ID.
+#[derive(Default)]
+pub struct ResolverChain {
+    resolvers: Vec<Box<dyn BoardResolver>>,
+    cache: RwLock<HashMap<String, ObfBoard>>,
+}
+
+impl ResolverChain {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a resolver to the end of the chain.
+    pub fn with(mut self, resolver: impl BoardResolver + 'static) -> Self {
+        self.resolvers.push(Box::new(resolver));
+        self
+    }
+
+    /// Pre-populate the cache with a board, e.g. one loaded from an OBZ package.
+    pub fn insert(&self, board: ObfBoard) {
+        self.cache.write().unwrap().insert(id_key(&board.id), board);
+    }
+
+    /// Drop all cached boards.
+    pub fn clear_cache(&self) {
+        self.cache.write().unwrap().clear();
+    }
+
+    /// Get the number of cached entries.
+    pub fn cached_count(&self) -> usize {
+        self.cache.read().unwrap().len()
+    }
+
+    /// Resolve a link, returning an error if no resolver can handle it.
+    pub fn resolve_link(&self, link: &ObfLoadBoard) -> Result<ObfBoard> {
+        self.resolve(link)?
+            .ok_or_else(|| BoardError::UnresolvedLink(describe(link)).into())
+    }
+
+    /// Resolve every board reachable from `root`, including `root` itself.
+    ///
+    /// Each board is visited once, so sets where boards link back to each
+    /// other terminate. Links that cannot be resolved are returned as an error.
+    pub fn resolve_all(&self, root: ObfBoard) -> Result<Vec<ObfBoard>> {
+        let mut visited = HashSet::new();
+        visited.insert(root.id.clone());
+        let mut boards = vec![root];
+        let mut next = 0;
+
+        while next < boards.len() {
+            let links: Vec<ObfLoadBoard> = boards[next]
+                .buttons
+                .iter()
+                .filter_map(|b| b.load_board.clone())
+                .collect();
+            next += 1;
+
+            for link in links {
+                if link.id.as_ref().is_some_and(|id| visited.contains(id)) {
+                    continue;
+                }
+                let board = self.resolve_link(&link)?;
+                if visited.insert(board.id.clone()) {
+                    boards.push(board);
+                }
+            }
+        }
+
+        Ok(boards)
+    }
+
+    fn cached(&self, link: &ObfLoadBoard) -> Option<ObfBoard> {
+        let cache = self.cache.read().unwrap();
+        link_keys(link)
+            .iter()
+            .find_map(|key| cache.get(key).cloned())
+    }
+}
+
+impl BoardResolver for ResolverChain {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        if let Some(board) = self.cached(link) {
+            return Ok(Some(board));
+        }
+
+        for resolver in &self.resolvers {
+            if let Some(board) = resolver.resolve(link)? {
+                if link.data_url.is_some() {
+                    return Ok(Some(board));
+                }
+                let mut cache = self.cache.write().unwrap();
+                for key in link_keys(link) {
+                    cache.insert(key, board.clone());
+                }
+                cache.insert(id_key(&board.id), board.clone());
+                return Ok(Some(board));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<R: BoardResolver + ?Sized> BoardResolver for Arc<R> {
+    fn resolve(&self, link: &ObfLoadBoard) -> Result<Option<ObfBoard>> {
+        (**self).resolve(link)
+    }
+}
+
+fn id_key(id: &str) -> String {
+    format!("id:{}", id)
+}
+
+/// Cache keys for a link. Inline data URLs are not cached.
+fn link_keys(link: &ObfLoadBoard) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(id) = &link.id {
+        keys.push(id_key(id));
+    }
+    if let Some(path) = &link.path {
+        keys.push(format!("path:{}", path));
+    }
+    if let Some(url) = &link.url {
+        keys.push(format!("url:{}", url));
+    }
+    keys
+}
+
+/// Short description of a link for error messages.
+pub(super) fn describe(link: &ObfLoadBoard) -> String {
+    if let Some(id) = &link.id {
+        format!("id '{}'", id)
+    } else if let Some(path) = &link.path {
+        format!("path '{}'", path)
+    } else if let Some(url) = &link.url {
+        format!("url '{}'", url)
+    } else if link.data_url.is_some() {
+        "inline data URL".to_string()
+    } else {
+        "empty link".to_string()
+    }
+}
+
+/// Decode the payload of a `data:` URL.
+fn decode_data_url(data_url: &str) -> Result<Vec<u8>> {
+    let invalid = |msg: &str| -> LoveWordsError { ObfError::InvalidData(msg.to_string()).into() };
+
+    let rest = data_url
+        .strip_prefix("data:")
+        .ok_or_else(|| invalid("data URL must start with 'data:'"))?;
+    let (meta, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| invalid("data URL is missing ','"))?;
+
+    if meta.ends_with(";base64") {
+        decode_base64(payload).ok_or_else(|| invalid("data URL has invalid base64"))
+    } else {
+        decode_percent(payload).ok_or_else(|| invalid("data URL has invalid percent-encoding"))
+    }
+}
+
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        buffer = (buffer << 6) | value(c)?;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn decode_percent(input: &str) -> Option<Vec<u8>> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obf::ObfButton;
+    use crate::storage::MemoryStorage;
+
+    fn link_id(id: &str) -> ObfLoadBoard {
+        ObfLoadBoard {
+            id: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_storage_resolver() {
+        let storage = Arc::new(MemoryStorage::with_boards(vec![ObfBoard::new("a", 1, 1)]));
+        let resolver = StorageResolver::new(storage);
+
+        assert_eq!(resolver.resolve(&link_id("a")).unwrap().unwrap().id, "a");
+        assert!(resolver.resolve(&link_id("missing")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_path_resolver() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("boards")).unwrap();
+        let board = ObfBoard::new("on-disk", 1, 1);
+        std::fs::write(
+            dir.path().join("boards/on-disk.obf"),
+            serde_json::to_vec(&board).unwrap(),
+        )
+        .unwrap();
+
+        let resolver = PathResolver::new(dir.path());
+        let link = ObfLoadBoard {
+            path: Some("boards/on-disk.obf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(resolver.resolve(&link).unwrap().unwrap().id, "on-disk");
+    }
+
+    #[test]
+    fn test_path_resolver_stays_in_base_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = ObfBoard::new("outside", 1, 1);
+        std::fs::write(
+            dir.path().join("outside.obf"),
+            serde_json::to_vec(&board).unwrap(),
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("boards")).unwrap();
+
+        let resolver = PathResolver::new(dir.path().join("boards"));
+        for path in ["../outside.obf", "sub/../../outside.obf", "/../outside.obf"] {
+            let link = ObfLoadBoard {
+                path: Some(path.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                matches!(
+                    resolver.resolve(&link),
+                    Err(LoveWordsError::Board(BoardError::UnresolvedLink(_)))
+                ),
+                "{path} was not rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_data_url_resolver() {
+        let json =
+            r#"{"format":"open-board-0.1","id":"inline","grid":{"rows":0,"columns":0,"order":[]}}"#;
+
+        let plain = ObfLoadBoard {
+            data_url: Some(format!(
+                "data:application/json,{}",
+                json.replace('"', "%22")
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            DataUrlResolver.resolve(&plain).unwrap().unwrap().id,
+            "inline"
+        );
+
+        // base64 of {"format":"open-board-0.1","id":"b64","grid":{"rows":0,"columns":0,"order":[]}}
+        let encoded = "eyJmb3JtYXQiOiJvcGVuLWJvYXJkLTAuMSIsImlkIjoiYjY0IiwiZ3JpZCI6eyJyb3dzIjowLCJjb2x1bW5zIjowLCJvcmRlciI6W119fQ==";
+        let b64 = ObfLoadBoard {
+            data_url: Some(format!("data:application/json;base64,{}", encoded)),
+            ..Default::default()
+        };
+        assert_eq!(DataUrlResolver.resolve(&b64).unwrap().unwrap().id, "b64");
+    }
+
+    #[test]
+    fn test_url_resolver_with_local_fetcher() {
+        let remote = serde_json::to_vec(&ObfBoard::new("remote", 1, 1)).unwrap();
+        let resolver = UrlResolver::new(move |url: &str| -> Result<Vec<u8>> {
+            if url == "https://boards.example/remote.obf" {
+                Ok(remote.clone())
+            } else {
+                Err(StorageError::BoardNotFound(url.to_string()).into())
+            }
+        });
+
+        let link = ObfLoadBoard {
+            url: Some("https://boards.example/remote.obf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(resolver.resolve(&link).unwrap().unwrap().id, "remote");
+    }
+
+    #[test]
+    fn test_chain_caches_by_id() {
+        let storage = Arc::new(MemoryStorage::with_boards(vec![ObfBoard::new("a", 1, 1)]));
+        let chain = ResolverChain::new().with(StorageResolver::new(storage.clone()));
+
+        chain.resolve_link(&link_id("a")).unwrap();
+        storage.clear();
+
+        // Still served from the cache after the storage is emptied
+        assert_eq!(chain.resolve_link(&link_id("a")).unwrap().id, "a");
+    }
+
+    #[test]
+    fn test_chain_falls_through_to_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(MemoryStorage::new());
+        let remote = serde_json::to_vec(&ObfBoard::new("remote", 1, 1)).unwrap();
+        let chain = ResolverChain::new()
+            .with(StorageResolver::new(storage))
+            .with(PathResolver::new(dir.path()))
+            .with(UrlResolver::new(move |_: &str| -> Result<Vec<u8>> {
+                Ok(remote.clone())
+            }));
+
+        // Neither the id nor the path exist locally
+        let link = ObfLoadBoard {
+            id: Some("remote".to_string()),
+            path: Some("boards/remote.obf".to_string()),
+            url: Some("https://boards.example/remote.obf".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(chain.resolve_link(&link).unwrap().id, "remote");
+    }
+
+    #[test]
+    fn test_chain_skips_caching_data_urls() {
+        let storage = Arc::new(MemoryStorage::with_boards(vec![ObfBoard::new(
+            "home", 2, 2,
+        )]));
+        let chain = ResolverChain::new()
+            .with(StorageResolver::new(storage))
+            .with(DataUrlResolver);
+
+        // An inline board reusing the ID "home"
+        let json = serde_json::to_string(&ObfBoard::new("home", 1, 1)).unwrap();
+        let inline = ObfLoadBoard {
+            data_url: Some(format!("data:application/json,{}", json)),
+            ..Default::default()
+        };
+        assert_eq!(chain.resolve_link(&inline).unwrap().grid.rows, 1);
+        assert_eq!(chain.cached_count(), 0);
+        assert_eq!(chain.resolve_link(&link_id("home")).unwrap().grid.rows, 2);
+    }
+
+    #[test]
+    fn test_chain_unresolved() {
+        let chain = ResolverChain::new().with(DataUrlResolver);
+        let result = chain.resolve_link(&link_id("nowhere"));
+        assert!(matches!(
+            result,
+            Err(LoveWordsError::Board(BoardError::UnresolvedLink(_)))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_all_handles_cycles() {
+        let mut a = ObfBoard::new("a", 1, 1);
+        a.add_button(ObfButton::navigate("to_b", "B", "b"));
+        a.place_button_at("to_b", 0, 0);
+        let mut b = ObfBoard::new("b", 1, 1);
+        b.add_button(ObfButton::navigate("to_a", "A", "a"));
+        b.place_button_at("to_a", 0, 0);
+
+        let storage = Arc::new(MemoryStorage::with_boards(vec![a.clone(), b]));
+        let chain = ResolverChain::new().with(StorageResolver::new(storage));
+
+        let boards = chain.resolve_all(a).unwrap();
+        let ids: Vec<_> = boards.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+}
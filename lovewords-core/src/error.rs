@@ -52,6 +52,10 @@ pub enum BoardError {
     #[error("Invalid grid: {0}")]
     InvalidGrid(String),
 
+    /// A board link could not be resolved to a board.
+    #[error("Cannot resolve board link: {0}")]
+    UnresolvedLink(String),
+
     /// Navigation stack is empty when trying to go back.
     #[error("Cannot navigate back: already at root board")]
     NavigationStackEmpty,
@@ -119,6 +123,18 @@ pub enum ObfError {
     /// JSON parsing error.
     #[error("JSON parse error: {0}")]
     JsonParse(#[from] serde_json::Error),
+
+    /// A file referenced by an OBZ package is missing from the archive.
+    #[error("Missing package entry: {0}")]
+    MissingEntry(String),
+
+    /// The OBZ archive could not be read or written.
+    #[error("Archive error: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    /// An I/O error occurred while reading or writing OBF data.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Errors related to input handling.
@@ -135,6 +151,10 @@ pub enum InputError {
     /// Input device not available.
     #[error("Input device unavailable: {0}")]
     DeviceUnavailable(String),
+
+    /// A recorded input trace could not be parsed or written.
+    #[error("Invalid input trace at line {line}: {message}")]
+    InvalidTrace { line: usize, message: String },
 }
 
 #[cfg(test)]
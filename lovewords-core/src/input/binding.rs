@@ -0,0 +1,272 @@
+//! Mapping physical keys, buttons and switches to logical input.
+//!
+//! Switch interfaces, keyboards and gamepads all report different physical
+//! inputs: one switch box types Space and Enter, another types 1 and 2, a
+//! third shows up as a joystick. A [`BindingMap`] stored in the profile maps
+//! each [`PhysicalInput`] to a [`KeyEvent`] or a switch id, and gives every
+//! switch its [`SwitchAction`], so any number of switches can be used.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lovewords_core::input::{BindingMap, BindingTarget, InputEvent, PhysicalInput};
+//!
+//! let mut bindings = BindingMap::default();
+//! bindings.bind(PhysicalInput::key("F13"), BindingTarget::Switch(2));
+//!
+//! assert_eq!(
+//!     bindings.press(&PhysicalInput::key("f13")),
+//!     Some(InputEvent::SwitchPress { switch_id: 2 })
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::SwitchAction;
+
+use super::event::{InputEvent, KeyEvent};
+
+/// A physical input reported by the platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhysicalInput {
+    /// A keyboard key, by name (e.g. "Space", "Enter", "1", "F13").
+    ///
+    /// Key names are compared case-insensitively.
+    Key(String),
+    /// A mouse button (0 = left, 1 = right, 2 = middle, ...).
+    MouseButton(u8),
+    /// A gamepad or joystick button.
+    GamepadButton(u8),
+}
+
+impl PhysicalInput {
+    /// Create a keyboard key input.
+    pub fn key(name: impl Into<String>) -> Self {
+        Self::Key(name.into())
+    }
+
+    /// Check if two inputs refer to the same physical key or button.
+    pub fn matches(&self, other: &PhysicalInput) -> bool {
+        match (self, other) {
+            (Self::Key(a), Self::Key(b)) => a.trim().eq_ignore_ascii_case(b.trim()),
+            _ => self == other,
+        }
+    }
+}
+
+/// What a physical input is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingTarget {
+    /// A keyboard navigation event.
+    Key(KeyEvent),
+    /// A switch, by id (0 = primary, 1 = secondary, ...).
+    Switch(u8),
+}
+
+/// A single binding from a physical input to a target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    /// The physical input.
+    pub input: PhysicalInput,
+    /// What it is bound to.
+    pub target: BindingTarget,
+}
+
+/// Table of input bindings and switch actions.
+///
+/// The default table binds the arrow keys, Tab, Enter, Space and Escape to
+/// keyboard navigation, and 1..4 to switches 0..3, which covers most switch
+/// interfaces out of the box. Switch boxes that type Space need it bound to
+/// a switch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BindingMap {
+    /// Bindings, checked in order.
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+
+    /// Actions for switches beyond the profile's primary and secondary
+    /// switch, or overriding them.
+    #[serde(default)]
+    pub switch_actions: BTreeMap<u8, SwitchAction>,
+}
+
+impl Default for BindingMap {
+    fn default() -> Self {
+        let keys = [
+            ("ArrowUp", KeyEvent::Up),
+            ("ArrowDown", KeyEvent::Down),
+            ("ArrowLeft", KeyEvent::Left),
+            ("ArrowRight", KeyEvent::Right),
+            ("Tab", KeyEvent::Tab),
+            ("Enter", KeyEvent::Enter),
+            ("Space", KeyEvent::Space),
+            ("Escape", KeyEvent::Escape),
+        ];
+        let switches = [("1", 0), ("2", 1), ("3", 2), ("4", 3)];
+
+        let mut map = Self::new();
+        for (name, key) in keys {
+            map.bind(PhysicalInput::key(name), BindingTarget::Key(key));
+        }
+        for (name, switch_id) in switches {
+            map.bind(PhysicalInput::key(name), BindingTarget::Switch(switch_id));
+        }
+        map
+    }
+}
+
+impl BindingMap {
+    /// Create an empty binding table.
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            switch_actions: BTreeMap::new(),
+        }
+    }
+
+    /// Add a binding, replacing any existing binding for the same input.
+    pub fn with_binding(mut self, input: PhysicalInput, target: BindingTarget) -> Self {
+        self.bind(input, target);
+        self
+    }
+
+    /// Set the action for a switch.
+    pub fn with_switch_action(mut self, switch_id: u8, action: SwitchAction) -> Self {
+        self.switch_actions.insert(switch_id, action);
+        self
+    }
+
+    /// Bind an input, replacing any existing binding for it.
+    pub fn bind(&mut self, input: PhysicalInput, target: BindingTarget) {
+        self.unbind(&input);
+        self.bindings.push(Binding { input, target });
+    }
+
+    /// Remove the binding for an input.
+    ///
+    /// Returns the target it was bound to, if any.
+    pub fn unbind(&mut self, input: &PhysicalInput) -> Option<BindingTarget> {
+        let index = self.bindings.iter().position(|b| b.input.matches(input))?;
+        Some(self.bindings.remove(index).target)
+    }
+
+    /// Get the target an input is bound to.
+    pub fn target(&self, input: &PhysicalInput) -> Option<BindingTarget> {
+        self.bindings
+            .iter()
+            .find(|b| b.input.matches(input))
+            .map(|b| b.target)
+    }
+
+    /// Get every input bound to a target.
+    pub fn inputs_for(&self, target: BindingTarget) -> Vec<&PhysicalInput> {
+        self.bindings
+            .iter()
+            .filter(|b| b.target == target)
+            .map(|b| &b.input)
+            .collect()
+    }
+
+    /// Get the action set for a switch in this table.
+    pub fn switch_action(&self, switch_id: u8) -> Option<SwitchAction> {
+        self.switch_actions.get(&switch_id).copied()
+    }
+
+    /// Translate a physical input going down into an input event.
+    pub fn press(&self, input: &PhysicalInput) -> Option<InputEvent> {
+        match self.target(input)? {
+            BindingTarget::Key(key) => Some(InputEvent::Key(key)),
+            BindingTarget::Switch(switch_id) => Some(InputEvent::SwitchPress { switch_id }),
+        }
+    }
+
+    /// Translate a physical input going up into an input event.
+    ///
+    /// Only switches report releases; keys act on press.
+    pub fn release(&self, input: &PhysicalInput) -> Option<InputEvent> {
+        match self.target(input)? {
+            BindingTarget::Key(_) => None,
+            BindingTarget::Switch(switch_id) => Some(InputEvent::SwitchRelease { switch_id }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings() {
+        let map = BindingMap::default();
+        assert_eq!(
+            map.press(&PhysicalInput::key("1")),
+            Some(InputEvent::switch_press())
+        );
+        assert_eq!(
+            map.press(&PhysicalInput::key("space")),
+            Some(InputEvent::Key(KeyEvent::Space))
+        );
+        assert_eq!(
+            map.press(&PhysicalInput::key("2")),
+            Some(InputEvent::switch_secondary())
+        );
+        assert_eq!(
+            map.press(&PhysicalInput::key("Enter")),
+            Some(InputEvent::Key(KeyEvent::Enter))
+        );
+        assert_eq!(map.release(&PhysicalInput::key("Enter")), None);
+        assert_eq!(map.release(&PhysicalInput::key("Space")), None);
+        assert_eq!(
+            map.release(&PhysicalInput::key("1")),
+            Some(InputEvent::SwitchRelease { switch_id: 0 })
+        );
+        assert_eq!(map.press(&PhysicalInput::GamepadButton(0)), None);
+    }
+
+    #[test]
+    fn test_rebinding() {
+        let mut map = BindingMap::new()
+            .with_binding(PhysicalInput::GamepadButton(0), BindingTarget::Switch(0))
+            .with_binding(PhysicalInput::GamepadButton(1), BindingTarget::Switch(4))
+            .with_switch_action(4, SwitchAction::Speak);
+
+        map.bind(PhysicalInput::GamepadButton(0), BindingTarget::Switch(1));
+        assert_eq!(map.bindings.len(), 2);
+        assert_eq!(
+            map.target(&PhysicalInput::GamepadButton(0)),
+            Some(BindingTarget::Switch(1))
+        );
+        assert_eq!(
+            map.inputs_for(BindingTarget::Switch(4)),
+            vec![&PhysicalInput::GamepadButton(1)]
+        );
+        assert_eq!(map.switch_action(4), Some(SwitchAction::Speak));
+
+        assert_eq!(
+            map.unbind(&PhysicalInput::GamepadButton(1)),
+            Some(BindingTarget::Switch(4))
+        );
+        assert_eq!(map.press(&PhysicalInput::GamepadButton(1)), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let map = BindingMap::new()
+            .with_binding(PhysicalInput::key("F13"), BindingTarget::Switch(2))
+            .with_binding(
+                PhysicalInput::MouseButton(1),
+                BindingTarget::Key(KeyEvent::Escape),
+            )
+            .with_switch_action(2, SwitchAction::Next);
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert!(json.contains(r#"{"input":{"key":"F13"},"target":{"switch":2}}"#));
+
+        let restored: BindingMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, map);
+    }
+}
@@ -0,0 +1,81 @@
+//! Time sources for timed input methods.
+//!
+//! Timestamps are [`Duration`]s since an arbitrary starting point, so input
+//! timing can be driven by a real clock in apps and by a [`ManualClock`] in
+//! tests without sleeping.
+
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// A monotonic time source.
+pub trait Clock: Send + Sync {
+    /// Get the current time since the clock's starting point.
+    fn now(&self) -> Duration;
+}
+
+/// Clock backed by [`Instant`], starting at zero when created.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock starting now.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when told to, for deterministic tests.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use lovewords_core::input::{Clock, ManualClock};
+///
+/// let clock = ManualClock::new();
+/// clock.advance(Duration::from_millis(250));
+/// assert_eq!(clock.now(), Duration::from_millis(250));
+/// ```
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: RwLock<Duration>,
+}
+
+impl ManualClock {
+    /// Create a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the current time.
+    pub fn set(&self, now: Duration) {
+        *self.now.write().unwrap() = now;
+    }
+
+    /// Move the clock forward.
+    pub fn advance(&self, by: Duration) {
+        *self.now.write().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.read().unwrap()
+    }
+}
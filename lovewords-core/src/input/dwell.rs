@@ -0,0 +1,273 @@
+//! Dwell selection from pointer and gaze coordinates.
+//!
+//! [`DwellTracker`] turns a stream of timestamped screen coordinates into
+//! [`InputEvent::Dwell`] events: the pointer selects a cell by resting on it
+//! for the dwell time. Head-pointer, eye-gaze and mouse clients feed the same
+//! tracker, so they all select at exactly the same moment.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::{DwellConfig, DwellTracker, GridGeometry, Rect};
+//!
+//! let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 400.0, 400.0), 4, 4);
+//! let mut tracker = DwellTracker::new(geometry, DwellConfig::default());
+//!
+//! let update = tracker.update(50.0, 50.0, Duration::ZERO);
+//! assert_eq!(update.target, Some((0, 0)));
+//! assert!(update.event.is_none());
+//!
+//! let update = tracker.update(55.0, 48.0, Duration::from_millis(500));
+//! assert_eq!(update.progress, 0.5);
+//!
+//! let update = tracker.update(52.0, 51.0, Duration::from_millis(1000));
+//! assert!(update.event.is_some());
+//! ```
+
+use std::time::Duration;
+
+use crate::storage::AccessibilitySettings;
+
+use super::event::InputEvent;
+use super::geometry::GridGeometry;
+
+/// Dwell selection configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DwellConfig {
+    /// Time the pointer must rest on a cell to select it.
+    pub dwell_time: Duration,
+
+    /// Time before the same cell can be selected again.
+    pub cooldown: Duration,
+
+    /// How far the pointer may drift outside the hovered cell, in pixels,
+    /// without restarting the dwell.
+    pub tolerance: f32,
+}
+
+impl Default for DwellConfig {
+    fn default() -> Self {
+        Self::from_settings(&AccessibilitySettings::default())
+    }
+}
+
+impl DwellConfig {
+    /// Build a dwell configuration from a profile's accessibility settings.
+    pub fn from_settings(settings: &AccessibilitySettings) -> Self {
+        Self {
+            dwell_time: settings.dwell_time,
+            cooldown: settings.dwell_cooldown,
+            tolerance: settings.dwell_tolerance.max(0.0),
+        }
+    }
+}
+
+/// Result of feeding one pointer sample to a [`DwellTracker`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DwellUpdate {
+    /// Cell currently being dwelled on, if any.
+    pub target: Option<(usize, usize)>,
+
+    /// How far the dwell has progressed, from 0.0 to 1.0.
+    ///
+    /// Clients use this to draw a fill animation on the target cell.
+    pub progress: f32,
+
+    /// Dwell event, emitted when the dwell time is reached.
+    pub event: Option<InputEvent>,
+}
+
+/// Tracks the hovered cell and emits dwell selections.
+///
+/// Once a dwell has started, the pointer may wander up to
+/// [`DwellConfig::tolerance`] pixels outside the cell before the dwell
+/// restarts, which absorbs tremor and gaze jitter. After a selection the
+/// same cell stays inactive for [`DwellConfig::cooldown`], whether the
+/// pointer rests on it or leaves and comes back.
+#[derive(Debug, Clone)]
+pub struct DwellTracker {
+    geometry: GridGeometry,
+    config: DwellConfig,
+    target: Option<(usize, usize)>,
+    started: Duration,
+    last_selected: Option<((usize, usize), Duration)>,
+}
+
+impl DwellTracker {
+    /// Create a tracker for a grid.
+    pub fn new(geometry: GridGeometry, config: DwellConfig) -> Self {
+        Self {
+            geometry,
+            config,
+            target: None,
+            started: Duration::ZERO,
+            last_selected: None,
+        }
+    }
+
+    /// Get the grid geometry.
+    pub fn geometry(&self) -> &GridGeometry {
+        &self.geometry
+    }
+
+    /// Replace the grid geometry (e.g. after a layout change or new board).
+    ///
+    /// Any dwell in progress is cancelled.
+    pub fn set_geometry(&mut self, geometry: GridGeometry) {
+        self.geometry = geometry;
+        self.reset();
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &DwellConfig {
+        &self.config
+    }
+
+    /// Replace the configuration.
+    pub fn set_config(&mut self, config: DwellConfig) {
+        self.config = config;
+    }
+
+    /// Get the cell currently being dwelled on.
+    pub fn target(&self) -> Option<(usize, usize)> {
+        self.target
+    }
+
+    /// Get the dwell progress at `now`, from 0.0 to 1.0.
+    pub fn progress(&self, now: Duration) -> f32 {
+        if self.target.is_none() {
+            return 0.0;
+        }
+        let elapsed = now.saturating_sub(self.started);
+        if self.config.dwell_time.is_zero() {
+            return 1.0;
+        }
+        (elapsed.as_secs_f32() / self.config.dwell_time.as_secs_f32()).min(1.0)
+    }
+
+    /// Feed a pointer sample taken at `now`.
+    pub fn update(&mut self, x: f32, y: f32, now: Duration) -> DwellUpdate {
+        if !self.is_holding(x, y) {
+            match self.geometry.cell_at(x, y) {
+                Some(cell) => self.begin(cell, now),
+                None => self.target = None,
+            }
+        }
+
+        let event = self.target.and_then(|(row, col)| {
+            let elapsed = now.checked_sub(self.started)?;
+            if elapsed < self.config.dwell_time {
+                return None;
+            }
+            self.last_selected = Some(((row, col), now));
+            self.started = now + self.config.cooldown;
+            Some(InputEvent::dwell(row, col, elapsed))
+        });
+
+        DwellUpdate {
+            target: self.target,
+            progress: if event.is_some() {
+                1.0
+            } else {
+                self.progress(now)
+            },
+            event,
+        }
+    }
+
+    /// Forget the pointer (e.g. it left the window or tracking was lost).
+    pub fn reset(&mut self) {
+        self.target = None;
+    }
+
+    fn is_holding(&self, x: f32, y: f32) -> bool {
+        self.target
+            .and_then(|(row, col)| self.geometry.cell_rect(row, col))
+            .is_some_and(|rect| rect.expand(self.config.tolerance).contains(x, y))
+    }
+
+    fn begin(&mut self, cell: (usize, usize), now: Duration) {
+        self.target = Some(cell);
+        self.started = match self.last_selected {
+            Some((selected, at)) if selected == cell => now.max(at + self.config.cooldown),
+            _ => now,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Rect;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn tracker() -> DwellTracker {
+        let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 200.0, 100.0), 1, 2);
+        DwellTracker::new(
+            geometry,
+            DwellConfig {
+                dwell_time: ms(1000),
+                cooldown: ms(500),
+                tolerance: 10.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_dwell_selects_at_threshold() {
+        let mut tracker = tracker();
+        assert!(tracker.update(50.0, 50.0, ms(0)).event.is_none());
+        assert_eq!(tracker.update(50.0, 50.0, ms(250)).progress, 0.25);
+
+        let update = tracker.update(50.0, 50.0, ms(1000));
+        assert_eq!(update.progress, 1.0);
+        assert_eq!(update.event, Some(InputEvent::dwell(0, 0, ms(1000))));
+    }
+
+    #[test]
+    fn test_drift_tolerance() {
+        let mut tracker = tracker();
+        tracker.update(95.0, 50.0, ms(0));
+
+        // Slightly into the next cell: still dwelling on the first
+        let update = tracker.update(105.0, 50.0, ms(600));
+        assert_eq!(update.target, Some((0, 0)));
+        assert_eq!(update.progress, 0.6);
+
+        // Beyond the tolerance: the dwell moves and restarts
+        let update = tracker.update(120.0, 50.0, ms(700));
+        assert_eq!(update.target, Some((0, 1)));
+        assert_eq!(update.progress, 0.0);
+
+        // Leaving the grid cancels the dwell
+        let update = tracker.update(300.0, 50.0, ms(800));
+        assert_eq!(update.target, None);
+        assert_eq!(update.progress, 0.0);
+    }
+
+    #[test]
+    fn test_cooldown_after_selection() {
+        let mut tracker = tracker();
+        tracker.update(50.0, 50.0, ms(0));
+        assert!(tracker.update(50.0, 50.0, ms(1000)).event.is_some());
+
+        // Resting on the cell: no progress during the cooldown
+        assert_eq!(tracker.update(50.0, 50.0, ms(1400)).progress, 0.0);
+        assert!(tracker.update(50.0, 50.0, ms(2400)).event.is_none());
+        assert!(tracker.update(50.0, 50.0, ms(2500)).event.is_some());
+
+        // Leaving and returning also waits out the cooldown
+        tracker.update(150.0, 50.0, ms(2600));
+        tracker.update(50.0, 50.0, ms(2700));
+        assert!(tracker.update(50.0, 50.0, ms(3700)).event.is_none());
+        assert!(tracker.update(50.0, 50.0, ms(4000)).event.is_some());
+
+        // Other cells are not affected
+        tracker.update(150.0, 50.0, ms(4100));
+        assert!(tracker.update(150.0, 50.0, ms(5100)).event.is_some());
+    }
+}
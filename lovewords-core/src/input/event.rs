@@ -5,16 +5,25 @@
 
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+
+use crate::storage::duration_millis;
+
 /// An input event from any source.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InputEvent {
     /// Direct tap/click on a cell at (row, col).
     Tap { row: usize, col: usize },
 
+    /// Two quick taps on the same cell.
+    DoubleTap { row: usize, col: usize },
+
     /// Long press on a cell.
     LongPress {
         row: usize,
         col: usize,
+        #[serde(with = "duration_millis")]
         duration: Duration,
     },
 
@@ -22,6 +31,7 @@ pub enum InputEvent {
     Dwell {
         row: usize,
         col: usize,
+        #[serde(with = "duration_millis")]
         dwell_time: Duration,
     },
 
@@ -81,6 +91,7 @@ impl InputEvent {
     pub fn cell_position(&self) -> Option<(usize, usize)> {
         match self {
             InputEvent::Tap { row, col }
+            | InputEvent::DoubleTap { row, col }
             | InputEvent::LongPress { row, col, .. }
             | InputEvent::Dwell { row, col, .. } => Some((*row, *col)),
             _ => None,
@@ -89,7 +100,7 @@ impl InputEvent {
 }
 
 /// Keyboard navigation events.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KeyEvent {
     /// Move focus up.
     Up,
@@ -156,4 +167,15 @@ mod tests {
         assert!(KeyEvent::Enter.is_activation());
         assert!(!KeyEvent::Enter.is_navigation());
     }
+
+    #[test]
+    fn test_serialization() {
+        let event = InputEvent::dwell(1, 2, Duration::from_millis(800));
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"dwell":{"row":1,"col":2,"dwell_time":800}}"#);
+        assert_eq!(serde_json::from_str::<InputEvent>(&json).unwrap(), event);
+
+        let key: InputEvent = serde_json::from_str(r#"{"key":"Enter"}"#).unwrap();
+        assert_eq!(key, InputEvent::Key(KeyEvent::Enter));
+    }
 }
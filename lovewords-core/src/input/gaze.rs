@@ -0,0 +1,498 @@
+//! Eye-gaze fixation filtering.
+//!
+//! Raw eye-tracker samples jitter, drop out during blinks and jump during
+//! saccades. [`GazeFilter`] turns them into stable fixations and feeds those
+//! to a [`DwellTracker`], so eye-gaze users get [`InputEvent::Dwell`] events
+//! tuned per person instead of by the tracker vendor.
+//!
+//! The filter works on plain [`GazeSample`]s, so it is independent of the
+//! eye-tracking hardware, and recorded traces can be replayed from CSV with
+//! [`GazeSample::parse_csv`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::{
+//!     DwellConfig, GazeConfig, GazeFilter, GazeSample, GridGeometry, Rect,
+//! };
+//!
+//! let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 400.0, 400.0), 2, 2);
+//! let mut gaze = GazeFilter::new(geometry, GazeConfig::default(), DwellConfig::default());
+//!
+//! // Look at the top-right cell for a little over a second
+//! let mut selected = None;
+//! for i in 0..80 {
+//!     let sample = GazeSample::new(300.0, 100.0, Duration::from_millis(i * 16));
+//!     selected = selected.or(gaze.update(sample).event);
+//! }
+//! assert_eq!(selected.and_then(|e| e.cell_position()), Some((0, 1)));
+//! ```
+
+use std::time::Duration;
+
+use crate::error::InputError;
+
+use super::dwell::{DwellConfig, DwellTracker};
+use super::event::InputEvent;
+use super::geometry::GridGeometry;
+
+/// A single eye-tracker sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GazeSample {
+    /// Horizontal screen position.
+    pub x: f32,
+    /// Vertical screen position.
+    pub y: f32,
+    /// When the sample was taken.
+    pub timestamp: Duration,
+    /// Whether the tracker found the eyes (false during blinks).
+    pub valid: bool,
+}
+
+impl GazeSample {
+    /// Create a valid sample.
+    pub fn new(x: f32, y: f32, timestamp: Duration) -> Self {
+        Self {
+            x,
+            y,
+            timestamp,
+            valid: true,
+        }
+    }
+
+    /// Create a sample where the eyes were lost (e.g. a blink).
+    pub fn lost(timestamp: Duration) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            timestamp,
+            valid: false,
+        }
+    }
+
+    /// Parse a recorded gaze trace.
+    ///
+    /// Each line is `timestamp_ms,x,y,valid`, where `valid` is `1`/`0` or
+    /// `true`/`false` and may be omitted (valid). Coordinates of invalid
+    /// samples may be empty. A header line, blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn parse_csv(text: &str) -> Result<Vec<GazeSample>, InputError> {
+        let mut samples = Vec::new();
+        let mut first = true;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match Self::parse_line(line) {
+                Ok(sample) => samples.push(sample),
+                // The first line may be a header
+                Err(_) if first => {}
+                Err(message) => {
+                    return Err(InputError::InvalidTrace {
+                        line: index + 1,
+                        message,
+                    })
+                }
+            }
+            first = false;
+        }
+        Ok(samples)
+    }
+
+    fn parse_line(line: &str) -> Result<GazeSample, String> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 3 {
+            return Err(format!(
+                "expected at least 3 fields, found {}",
+                fields.len()
+            ));
+        }
+        let millis: f64 = fields[0]
+            .parse()
+            .map_err(|_| format!("invalid timestamp '{}'", fields[0]))?;
+        let timestamp = Duration::from_secs_f64(millis.max(0.0) / 1000.0);
+
+        let valid = match fields.get(3).copied().unwrap_or("1") {
+            "1" | "true" => true,
+            "0" | "false" => false,
+            other => return Err(format!("invalid validity '{}'", other)),
+        };
+        if !valid {
+            return Ok(GazeSample::lost(timestamp));
+        }
+
+        let coord = |field: &str| {
+            field
+                .parse::<f32>()
+                .map_err(|_| format!("invalid coordinate '{}'", field))
+        };
+        Ok(GazeSample::new(
+            coord(fields[1])?,
+            coord(fields[2])?,
+            timestamp,
+        ))
+    }
+}
+
+/// Fixation detection settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GazeConfig {
+    /// Largest spread of a fixation (width plus height), in pixels.
+    pub dispersion: f32,
+
+    /// Eye speed above which movement counts as a saccade, in pixels per
+    /// second.
+    pub velocity: f32,
+
+    /// Weight of each new sample in the smoothed position, from 0.0 (no
+    /// movement) to 1.0 (no smoothing).
+    pub smoothing: f32,
+
+    /// Shortest steady gaze that counts as a fixation.
+    pub min_fixation: Duration,
+
+    /// Longest loss of tracking treated as a blink; longer losses cancel
+    /// the dwell.
+    pub blink_tolerance: Duration,
+
+    /// How far outside a cell a fixation may land and still snap to it,
+    /// in pixels.
+    pub snap_distance: f32,
+}
+
+impl Default for GazeConfig {
+    fn default() -> Self {
+        Self {
+            dispersion: 60.0,
+            velocity: 1500.0,
+            smoothing: 0.3,
+            min_fixation: Duration::from_millis(100),
+            blink_tolerance: Duration::from_millis(300),
+            snap_distance: 30.0,
+        }
+    }
+}
+
+/// A detected fixation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fixation {
+    /// Horizontal center.
+    pub x: f32,
+    /// Vertical center.
+    pub y: f32,
+    /// When the fixation started.
+    pub start: Duration,
+    /// How long it has lasted.
+    pub duration: Duration,
+    /// Cell the fixation snapped to, if any.
+    pub cell: Option<(usize, usize)>,
+}
+
+/// Result of feeding one sample to a [`GazeFilter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GazeUpdate {
+    /// Current fixation, if the eyes are fixating.
+    pub fixation: Option<Fixation>,
+    /// Cell being dwelled on, if any.
+    pub target: Option<(usize, usize)>,
+    /// Dwell progress on the target, from 0.0 to 1.0.
+    pub progress: f32,
+    /// Dwell event, emitted when the dwell time is reached.
+    pub event: Option<InputEvent>,
+}
+
+/// Samples of the fixation candidate, kept as running bounds and sums.
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    start: Duration,
+    min: (f32, f32),
+    max: (f32, f32),
+    sum: (f32, f32),
+    count: u32,
+}
+
+impl Window {
+    fn new(x: f32, y: f32, start: Duration) -> Self {
+        Self {
+            start,
+            min: (x, y),
+            max: (x, y),
+            sum: (x, y),
+            count: 1,
+        }
+    }
+
+    /// Dispersion if the point were added.
+    fn dispersion_with(&self, x: f32, y: f32) -> f32 {
+        (self.max.0.max(x) - self.min.0.min(x)) + (self.max.1.max(y) - self.min.1.min(y))
+    }
+
+    fn add(&mut self, x: f32, y: f32) {
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+        self.sum = (self.sum.0 + x, self.sum.1 + y);
+        self.count += 1;
+    }
+
+    fn center(&self) -> (f32, f32) {
+        let n = self.count as f32;
+        (self.sum.0 / n, self.sum.1 / n)
+    }
+}
+
+/// Turns raw gaze samples into fixations and dwell selections.
+///
+/// Samples are smoothed, then grouped into fixations with a combined
+/// velocity and dispersion threshold: a fast jump (saccade) cancels the
+/// dwell, while a slow drift beyond the dispersion only starts a new
+/// fixation. Each fixation snaps to the nearest cell and drives the dwell
+/// on it. Blinks shorter than [`GazeConfig::blink_tolerance`] are ignored.
+#[derive(Debug, Clone)]
+pub struct GazeFilter {
+    config: GazeConfig,
+    dwell: DwellTracker,
+    smoothed: Option<(f32, f32)>,
+    last: Option<GazeSample>,
+    lost_since: Option<Duration>,
+    window: Option<Window>,
+}
+
+impl GazeFilter {
+    /// Create a filter for a grid.
+    pub fn new(geometry: GridGeometry, config: GazeConfig, dwell: DwellConfig) -> Self {
+        Self {
+            config,
+            dwell: DwellTracker::new(geometry, dwell),
+            smoothed: None,
+            last: None,
+            lost_since: None,
+            window: None,
+        }
+    }
+
+    /// Get the fixation settings.
+    pub fn config(&self) -> &GazeConfig {
+        &self.config
+    }
+
+    /// Get the dwell tracker.
+    pub fn dwell(&self) -> &DwellTracker {
+        &self.dwell
+    }
+
+    /// Replace the grid geometry, cancelling any dwell in progress.
+    pub fn set_geometry(&mut self, geometry: GridGeometry) {
+        self.dwell.set_geometry(geometry);
+        self.reset();
+    }
+
+    /// Forget all gaze history.
+    pub fn reset(&mut self) {
+        self.dwell.reset();
+        self.smoothed = None;
+        self.last = None;
+        self.lost_since = None;
+        self.window = None;
+    }
+
+    /// Feed one sample.
+    pub fn update(&mut self, sample: GazeSample) -> GazeUpdate {
+        let now = sample.timestamp;
+        if !sample.valid {
+            let since = *self.lost_since.get_or_insert(now);
+            if now.saturating_sub(since) > self.config.blink_tolerance {
+                self.reset();
+                self.lost_since = Some(since);
+            }
+            return self.status(now, None, None);
+        }
+        self.lost_since = None;
+
+        let saccade = self.last.is_some_and(|last| {
+            let dt = now.saturating_sub(last.timestamp).as_secs_f32();
+            let distance = (sample.x - last.x).hypot(sample.y - last.y);
+            dt > 0.0 && distance / dt > self.config.velocity
+        });
+        self.last = Some(sample);
+
+        let (x, y) = match (self.smoothed, saccade) {
+            (Some((sx, sy)), false) => {
+                let a = self.config.smoothing.clamp(0.0, 1.0);
+                (sx + (sample.x - sx) * a, sy + (sample.y - sy) * a)
+            }
+            _ => (sample.x, sample.y),
+        };
+        self.smoothed = Some((x, y));
+
+        if saccade {
+            self.dwell.reset();
+        }
+        match &mut self.window {
+            Some(window) if !saccade && window.dispersion_with(x, y) <= self.config.dispersion => {
+                window.add(x, y)
+            }
+            _ => self.window = Some(Window::new(x, y, now)),
+        }
+
+        let fixation = self.fixation(now);
+        let mut event = None;
+        match fixation {
+            Some(Fixation {
+                cell: Some((row, col)),
+                ..
+            }) => {
+                let rect = self.dwell.geometry().cell_rect(row, col);
+                if let Some((cx, cy)) = rect.map(|r| r.center()) {
+                    event = self.dwell.update(cx, cy, now).event;
+                }
+            }
+            Some(Fixation { cell: None, .. }) => self.dwell.reset(),
+            None => {}
+        }
+        self.status(now, fixation, event)
+    }
+
+    /// Feed a whole recorded trace, returning the dwell events it produces.
+    pub fn replay(&mut self, samples: &[GazeSample]) -> Vec<InputEvent> {
+        samples
+            .iter()
+            .filter_map(|sample| self.update(*sample).event)
+            .collect()
+    }
+
+    fn fixation(&self, now: Duration) -> Option<Fixation> {
+        let window = self
+            .window
+            .filter(|w| now.saturating_sub(w.start) >= self.config.min_fixation)?;
+        let (x, y) = window.center();
+        Some(Fixation {
+            x,
+            y,
+            start: window.start,
+            duration: now.saturating_sub(window.start),
+            cell: self
+                .dwell
+                .geometry()
+                .nearest_cell(x, y, self.config.snap_distance),
+        })
+    }
+
+    fn status(
+        &self,
+        now: Duration,
+        fixation: Option<Fixation>,
+        event: Option<InputEvent>,
+    ) -> GazeUpdate {
+        GazeUpdate {
+            fixation,
+            target: self.dwell.target(),
+            progress: if event.is_some() {
+                1.0
+            } else {
+                self.dwell.progress(now)
+            },
+            event,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Rect;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn filter() -> GazeFilter {
+        let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 420.0, 200.0), 1, 2).with_gap(20.0);
+        GazeFilter::new(
+            geometry,
+            GazeConfig::default(),
+            DwellConfig {
+                dwell_time: ms(500),
+                cooldown: ms(1000),
+                tolerance: 0.0,
+            },
+        )
+    }
+
+    /// Noisy samples around a point every 20ms.
+    fn look(x: f32, y: f32, from: u64, to: u64) -> Vec<GazeSample> {
+        (from..to)
+            .step_by(20)
+            .enumerate()
+            .map(|(i, t)| {
+                let jitter = if i % 2 == 0 { 8.0 } else { -8.0 };
+                GazeSample::new(x + jitter, y - jitter / 2.0, ms(t))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "timestamp_ms,x,y,valid\n0,10.5,20,1\n# blink\n16,,,0\n33,11,21\n";
+        let samples = GazeSample::parse_csv(csv).unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0], GazeSample::new(10.5, 20.0, ms(0)));
+        assert!(!samples[1].valid);
+        assert_eq!(samples[2].timestamp, ms(33));
+
+        let err = GazeSample::parse_csv("0,1,2\n16,x,2\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_fixation_with_jitter_selects() {
+        let mut gaze = filter();
+        let events = gaze.replay(&look(100.0, 100.0, 0, 800));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cell_position(), Some((0, 0)));
+
+        let update = gaze.update(GazeSample::new(100.0, 100.0, ms(820)));
+        let fixation = update.fixation.unwrap();
+        assert_eq!(fixation.cell, Some((0, 0)));
+        assert!((fixation.x - 100.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_saccade_restarts_dwell() {
+        let mut gaze = filter();
+        gaze.replay(&look(100.0, 100.0, 0, 400));
+        assert_eq!(gaze.dwell().target(), Some((0, 0)));
+
+        // A jump to the other cell cancels, then dwells there
+        let events = gaze.replay(&look(320.0, 100.0, 400, 1100));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cell_position(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_blinks() {
+        let mut gaze = filter();
+        let mut trace = look(100.0, 100.0, 0, 300);
+        trace.extend((300..450).step_by(20).map(|t| GazeSample::lost(ms(t))));
+        trace.extend(look(100.0, 100.0, 460, 700));
+
+        // A short blink doesn't interrupt the dwell
+        assert_eq!(gaze.replay(&trace).len(), 1);
+
+        // A long loss of tracking cancels it
+        let mut gaze = filter();
+        let mut trace = look(100.0, 100.0, 0, 300);
+        trace.extend((300..800).step_by(20).map(|t| GazeSample::lost(ms(t))));
+        assert!(gaze.replay(&trace).is_empty());
+        assert_eq!(gaze.dwell().target(), None);
+    }
+
+    #[test]
+    fn test_snaps_gap_to_nearest_cell() {
+        let mut gaze = filter();
+        // Looking at the gap, slightly closer to the left cell
+        let update = gaze.replay(&look(205.0, 100.0, 0, 800)).into_iter().next();
+        assert_eq!(update.and_then(|e| e.cell_position()), Some((0, 0)));
+    }
+}
@@ -0,0 +1,201 @@
+//! On-screen grid geometry for pointer-based input.
+//!
+//! Pointer, head-tracking and eye-gaze input arrive as screen coordinates.
+//! [`GridGeometry`] describes where the board's cells are drawn so those
+//! coordinates can be mapped back to grid positions the same way on every
+//! client.
+
+use serde::{Deserialize, Serialize};
+
+/// A rectangle in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Width.
+    pub width: f32,
+    /// Height.
+    pub height: f32,
+}
+
+impl Rect {
+    /// Create a rectangle.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Check if a point is inside the rectangle, edges included.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+
+    /// Grow the rectangle by `margin` on every side.
+    pub fn expand(&self, margin: f32) -> Rect {
+        Rect::new(
+            self.x - margin,
+            self.y - margin,
+            self.width + 2.0 * margin,
+            self.height + 2.0 * margin,
+        )
+    }
+
+    /// Get the center point.
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Get the distance from a point to the rectangle (zero if inside).
+    pub fn distance_to(&self, x: f32, y: f32) -> f32 {
+        let dx = (self.x - x).max(x - (self.x + self.width)).max(0.0);
+        let dy = (self.y - y).max(y - (self.y + self.height)).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+/// Where a board's grid is drawn on screen.
+///
+/// The grid fills `bounds` with `rows` x `cols` equally sized cells,
+/// separated by `gap` pixels. Points in the gaps belong to no cell.
+///
+/// # Example
+///
+/// ```rust
+/// use lovewords_core::input::{GridGeometry, Rect};
+///
+/// let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 400.0, 300.0), 3, 4);
+/// assert_eq!(geometry.cell_at(150.0, 250.0), Some((2, 1)));
+/// assert_eq!(geometry.cell_at(500.0, 10.0), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridGeometry {
+    /// Area covered by the grid.
+    pub bounds: Rect,
+    /// Number of rows.
+    pub rows: usize,
+    /// Number of columns.
+    pub cols: usize,
+    /// Space between neighbouring cells.
+    pub gap: f32,
+}
+
+impl GridGeometry {
+    /// Create a grid geometry with no gaps between cells.
+    pub fn new(bounds: Rect, rows: usize, cols: usize) -> Self {
+        Self {
+            bounds,
+            rows,
+            cols,
+            gap: 0.0,
+        }
+    }
+
+    /// Set the space between neighbouring cells.
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap.max(0.0);
+        self
+    }
+
+    /// Get the width of a single cell.
+    pub fn cell_width(&self) -> f32 {
+        Self::cell_size(self.bounds.width, self.cols, self.gap)
+    }
+
+    /// Get the height of a single cell.
+    pub fn cell_height(&self) -> f32 {
+        Self::cell_size(self.bounds.height, self.rows, self.gap)
+    }
+
+    /// Get the rectangle of a cell, or `None` if it is outside the grid.
+    pub fn cell_rect(&self, row: usize, col: usize) -> Option<Rect> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        let width = self.cell_width();
+        let height = self.cell_height();
+        Some(Rect::new(
+            self.bounds.x + col as f32 * (width + self.gap),
+            self.bounds.y + row as f32 * (height + self.gap),
+            width,
+            height,
+        ))
+    }
+
+    /// Get the cell under a point, if any.
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        if self.rows == 0 || self.cols == 0 || !self.bounds.contains(x, y) {
+            return None;
+        }
+        let col = Self::index(x - self.bounds.x, self.cell_width(), self.gap, self.cols);
+        let row = Self::index(y - self.bounds.y, self.cell_height(), self.gap, self.rows);
+        let (row, col) = (row?, col?);
+        self.cell_rect(row, col)?
+            .contains(x, y)
+            .then_some((row, col))
+    }
+
+    /// Get the cell nearest to a point, within `max_distance` of its edge.
+    pub fn nearest_cell(&self, x: f32, y: f32, max_distance: f32) -> Option<(usize, usize)> {
+        if let Some(cell) = self.cell_at(x, y) {
+            return Some(cell);
+        }
+        (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .filter_map(|(row, col)| {
+                let distance = self.cell_rect(row, col)?.distance_to(x, y);
+                (distance <= max_distance).then_some(((row, col), distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(cell, _)| cell)
+    }
+
+    fn cell_size(total: f32, count: usize, gap: f32) -> f32 {
+        if count == 0 {
+            return 0.0;
+        }
+        ((total - gap * (count - 1) as f32) / count as f32).max(0.0)
+    }
+
+    fn index(offset: f32, size: f32, gap: f32, count: usize) -> Option<usize> {
+        let pitch = size + gap;
+        if pitch <= 0.0 {
+            return None;
+        }
+        Some(((offset / pitch) as usize).min(count - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_lookup_with_gap() {
+        let geometry = GridGeometry::new(Rect::new(10.0, 20.0, 210.0, 100.0), 2, 2).with_gap(10.0);
+        assert_eq!(geometry.cell_width(), 100.0);
+        assert_eq!(geometry.cell_height(), 45.0);
+
+        assert_eq!(geometry.cell_at(15.0, 25.0), Some((0, 0)));
+        assert_eq!(geometry.cell_at(200.0, 110.0), Some((1, 1)));
+
+        // Points in the gap belong to no cell, but are near one
+        assert_eq!(geometry.cell_at(115.0, 30.0), None);
+        assert_eq!(geometry.nearest_cell(112.0, 30.0, 5.0), Some((0, 0)));
+        assert_eq!(geometry.nearest_cell(0.0, 0.0, 5.0), None);
+    }
+
+    #[test]
+    fn test_cell_rect() {
+        let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 300.0, 200.0), 2, 3);
+        let rect = geometry.cell_rect(1, 2).unwrap();
+        assert_eq!(rect, Rect::new(200.0, 100.0, 100.0, 100.0));
+        assert_eq!(rect.center(), (250.0, 150.0));
+        assert!(geometry.cell_rect(2, 0).is_none());
+    }
+}
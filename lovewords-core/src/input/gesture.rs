@@ -0,0 +1,407 @@
+//! Touch and pointer gesture recognition.
+//!
+//! [`GestureRecognizer`] turns raw press/move/release events into
+//! [`InputEvent::Tap`], [`InputEvent::DoubleTap`], [`InputEvent::LongPress`]
+//! and [`InputEvent::Scroll`] using the thresholds of a [`TimingConfig`], so
+//! every client recognizes gestures identically.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::accessibility::TimingConfig;
+//! use lovewords_core::input::{GestureRecognizer, GridGeometry, InputEvent, Rect};
+//!
+//! let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 400.0, 400.0), 4, 4);
+//! let mut gestures =
+//!     GestureRecognizer::new(geometry, TimingConfig::default()).with_double_tap(false);
+//!
+//! gestures.press(150.0, 50.0, Duration::ZERO);
+//! let events = gestures.release(150.0, 50.0, Duration::from_millis(120));
+//! assert_eq!(events, vec![InputEvent::tap(0, 1)]);
+//! ```
+
+use std::time::Duration;
+
+use crate::accessibility::TimingConfig;
+use crate::storage::InputSettings;
+
+use super::event::InputEvent;
+use super::geometry::GridGeometry;
+
+/// A press in progress.
+#[derive(Debug, Clone)]
+struct Press {
+    x: f32,
+    y: f32,
+    at: Duration,
+    cell: Option<(usize, usize)>,
+    moved: bool,
+    long_pressed: bool,
+    /// Whether this press may complete a double tap.
+    second: bool,
+}
+
+/// A tap held back while waiting for a possible second tap.
+#[derive(Debug, Clone, Copy)]
+struct PendingTap {
+    cell: (usize, usize),
+    released: Duration,
+}
+
+/// Recognizes taps, double taps, long presses and swipes.
+///
+/// Presses shorter than [`TimingConfig::tap_debounce`] are ignored as
+/// bounces. A long press fires once while the pointer is still held, as soon
+/// as [`TimingConfig::long_press_threshold`] is reached. A press that moves
+/// further than the swipe threshold becomes a [`InputEvent::Scroll`].
+///
+/// With double taps enabled (the default) a tap is held back until
+/// [`TimingConfig::double_tap_window`] has passed without a second tap;
+/// call [`update`](Self::update) regularly so it is released on time.
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    geometry: GridGeometry,
+    timing: TimingConfig,
+    swipe_threshold: f32,
+    double_tap: bool,
+    long_press: bool,
+    swipes: bool,
+    press: Option<Press>,
+    pending: Option<PendingTap>,
+}
+
+impl GestureRecognizer {
+    /// Default distance a press must travel to become a swipe, in pixels.
+    pub const DEFAULT_SWIPE_THRESHOLD: f32 = 40.0;
+
+    /// Create a recognizer for a grid with the given timing.
+    pub fn new(geometry: GridGeometry, timing: TimingConfig) -> Self {
+        Self {
+            geometry,
+            timing,
+            swipe_threshold: Self::DEFAULT_SWIPE_THRESHOLD,
+            double_tap: true,
+            long_press: true,
+            swipes: true,
+            press: None,
+            pending: None,
+        }
+    }
+
+    /// Set the distance a press must travel to become a swipe.
+    pub fn with_swipe_threshold(mut self, threshold: f32) -> Self {
+        self.swipe_threshold = threshold.max(0.0);
+        self
+    }
+
+    /// Enable or disable double-tap detection.
+    ///
+    /// Without it, taps are reported as soon as the pointer is released.
+    pub fn with_double_tap(mut self, enabled: bool) -> Self {
+        self.double_tap = enabled;
+        self
+    }
+
+    /// Apply input settings; see [`apply_input_settings`](Self::apply_input_settings).
+    pub fn with_input_settings(mut self, settings: &InputSettings) -> Self {
+        self.apply_input_settings(settings);
+        self
+    }
+
+    /// Apply a profile's long-press and swipe preferences.
+    ///
+    /// Only the enable flags are applied; the long-press threshold stays
+    /// with the timing preset.
+    pub fn apply_input_settings(&mut self, settings: &InputSettings) {
+        self.long_press = settings.long_press_enabled;
+        self.swipes = settings.swipe_navigation;
+    }
+
+    /// Get the timing configuration.
+    pub fn timing(&self) -> &TimingConfig {
+        &self.timing
+    }
+
+    /// Replace the timing configuration (e.g. switch to a preset).
+    pub fn set_timing(&mut self, timing: TimingConfig) {
+        self.timing = timing;
+    }
+
+    /// Get the grid geometry.
+    pub fn geometry(&self) -> &GridGeometry {
+        &self.geometry
+    }
+
+    /// Replace the grid geometry, cancelling any gesture in progress.
+    pub fn set_geometry(&mut self, geometry: GridGeometry) {
+        self.geometry = geometry;
+        self.reset();
+    }
+
+    /// Check if the pointer is currently pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    /// Forget any gesture in progress, including a held-back tap.
+    pub fn reset(&mut self) {
+        self.press = None;
+        self.pending = None;
+    }
+
+    /// Handle the pointer going down at `now`.
+    pub fn press(&mut self, x: f32, y: f32, now: Duration) -> Vec<InputEvent> {
+        let mut events = self.update(now);
+        let cell = self.geometry.cell_at(x, y);
+
+        let second = match self.pending {
+            Some(pending) if Some(pending.cell) == cell => true,
+            Some(_) => {
+                events.extend(self.flush());
+                false
+            }
+            None => false,
+        };
+
+        self.press = Some(Press {
+            x,
+            y,
+            at: now,
+            cell,
+            moved: false,
+            long_pressed: false,
+            second,
+        });
+        events
+    }
+
+    /// Handle the pointer moving while pressed.
+    pub fn move_to(&mut self, x: f32, y: f32, now: Duration) -> Vec<InputEvent> {
+        if let Some(press) = &mut self.press {
+            if !press.moved && (x - press.x).hypot(y - press.y) > self.swipe_threshold {
+                press.moved = true;
+            }
+        }
+        self.update(now)
+    }
+
+    /// Handle the pointer going up at `now`.
+    pub fn release(&mut self, x: f32, y: f32, now: Duration) -> Vec<InputEvent> {
+        let mut events = self.move_to(x, y, now);
+        let Some(press) = self.press.take() else {
+            return events;
+        };
+
+        if press.long_pressed {
+            return events;
+        }
+
+        if press.moved {
+            if press.second {
+                events.extend(self.flush());
+            }
+            if self.swipes {
+                events.push(InputEvent::Scroll {
+                    dx: (x - press.x).round() as i32,
+                    dy: (y - press.y).round() as i32,
+                });
+            }
+            return events;
+        }
+
+        if now.saturating_sub(press.at) < self.timing.tap_debounce {
+            return events;
+        }
+
+        match press.cell {
+            Some((row, col)) if press.second => {
+                self.pending = None;
+                events.push(InputEvent::DoubleTap { row, col });
+            }
+            Some(cell) if self.double_tap => {
+                self.pending = Some(PendingTap {
+                    cell,
+                    released: now,
+                });
+            }
+            Some((row, col)) => events.push(InputEvent::tap(row, col)),
+            None => {}
+        }
+        events
+    }
+
+    /// Report time-based gestures due at `now`.
+    ///
+    /// Fires a long press once the pointer has been held long enough, and
+    /// releases a held-back tap once the double-tap window has passed.
+    pub fn update(&mut self, now: Duration) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        if self
+            .pending
+            .is_some_and(|p| now.saturating_sub(p.released) > self.timing.double_tap_window)
+            && !self.press.as_ref().is_some_and(|p| p.second)
+        {
+            events.extend(self.flush());
+        }
+
+        let threshold = self.timing.long_press_threshold;
+        let long_press = self.long_press;
+        let Some(press) = &mut self.press else {
+            return events;
+        };
+        if !long_press || press.moved || press.long_pressed {
+            return events;
+        }
+        let held = now.saturating_sub(press.at);
+        let Some((row, col)) = press.cell.filter(|_| held >= threshold) else {
+            return events;
+        };
+
+        // A long press on the second tap leaves the first one as a tap
+        press.long_pressed = true;
+        if std::mem::take(&mut press.second) {
+            events.extend(self.flush());
+        }
+        events.push(InputEvent::LongPress {
+            row,
+            col,
+            duration: held,
+        });
+        events
+    }
+
+    fn flush(&mut self) -> Option<InputEvent> {
+        self.pending
+            .take()
+            .map(|p| InputEvent::tap(p.cell.0, p.cell.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Rect;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn recognizer(timing: TimingConfig) -> GestureRecognizer {
+        let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 200.0, 200.0), 2, 2);
+        GestureRecognizer::new(geometry, timing)
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut gestures = recognizer(TimingConfig::default()).with_double_tap(false);
+        gestures.press(50.0, 50.0, ms(0));
+        assert!(gestures.release(50.0, 50.0, ms(20)).is_empty());
+
+        gestures.press(50.0, 50.0, ms(100));
+        assert_eq!(
+            gestures.release(50.0, 50.0, ms(200)),
+            vec![InputEvent::tap(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut gestures = recognizer(TimingConfig::default());
+
+        // A single tap is released once the window passes
+        gestures.press(50.0, 50.0, ms(0));
+        assert!(gestures.release(50.0, 50.0, ms(100)).is_empty());
+        assert!(gestures.update(ms(300)).is_empty());
+        assert_eq!(gestures.update(ms(401)), vec![InputEvent::tap(0, 0)]);
+
+        // Two quick taps on the same cell
+        gestures.press(150.0, 50.0, ms(1000));
+        gestures.release(150.0, 50.0, ms(1100));
+        assert!(gestures.press(150.0, 50.0, ms(1300)).is_empty());
+        assert_eq!(
+            gestures.release(150.0, 50.0, ms(1400)),
+            vec![InputEvent::DoubleTap { row: 0, col: 1 }]
+        );
+        assert!(gestures.update(ms(2000)).is_empty());
+
+        // A tap on another cell releases the held-back tap
+        gestures.press(50.0, 50.0, ms(3000));
+        gestures.release(50.0, 50.0, ms(3100));
+        assert_eq!(
+            gestures.press(150.0, 150.0, ms(3200)),
+            vec![InputEvent::tap(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_long_press_fires_while_held() {
+        let mut gestures = recognizer(TimingConfig::default());
+        gestures.press(150.0, 150.0, ms(0));
+        assert!(gestures.update(ms(400)).is_empty());
+        assert_eq!(
+            gestures.update(ms(500)),
+            vec![InputEvent::LongPress {
+                row: 1,
+                col: 1,
+                duration: ms(500),
+            }]
+        );
+
+        // Only once, and no tap on release
+        assert!(gestures.update(ms(900)).is_empty());
+        assert!(gestures.release(150.0, 150.0, ms(1000)).is_empty());
+        assert!(gestures.update(ms(2000)).is_empty());
+    }
+
+    #[test]
+    fn test_swipe_becomes_scroll() {
+        let mut gestures = recognizer(TimingConfig::default());
+        gestures.press(20.0, 100.0, ms(0));
+        gestures.move_to(60.0, 100.0, ms(50));
+        assert_eq!(
+            gestures.release(120.0, 90.0, ms(100)),
+            vec![InputEvent::Scroll { dx: 100, dy: -10 }]
+        );
+
+        // Moving cancels the long press
+        gestures.press(20.0, 100.0, ms(1000));
+        gestures.move_to(100.0, 100.0, ms(1100));
+        assert!(gestures.update(ms(2000)).is_empty());
+
+        // Small tremor movements stay a tap
+        let mut gestures = gestures.with_double_tap(false);
+        gestures.reset();
+        gestures.press(50.0, 50.0, ms(3000));
+        gestures.move_to(60.0, 45.0, ms(3050));
+        assert_eq!(
+            gestures.release(55.0, 55.0, ms(3100)),
+            vec![InputEvent::tap(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_presets_and_settings() {
+        let mut gestures = recognizer(TimingConfig::relaxed());
+        gestures.press(50.0, 50.0, ms(0));
+        assert!(gestures.update(ms(600)).is_empty());
+        assert_eq!(gestures.update(ms(800)).len(), 1);
+
+        // Profile settings keep the preset's threshold
+        let gestures =
+            recognizer(TimingConfig::relaxed()).with_input_settings(&InputSettings::default());
+        assert_eq!(gestures.timing().long_press_threshold, ms(800));
+
+        let settings = InputSettings {
+            long_press_enabled: false,
+            swipe_navigation: false,
+            ..InputSettings::default()
+        };
+        let mut gestures = recognizer(TimingConfig::quick()).with_input_settings(&settings);
+        gestures.press(50.0, 50.0, ms(0));
+        assert!(gestures.update(ms(5000)).is_empty());
+        gestures.move_to(150.0, 50.0, ms(5100));
+        assert!(gestures.release(150.0, 50.0, ms(5200)).is_empty());
+    }
+}
@@ -0,0 +1,448 @@
+//! Filtering of accidental activations.
+//!
+//! Users with tremor or spasticity often brush cells or bump switches by
+//! accident. [`KeyGuard`] sits between the raw input and selection and
+//! applies the rules in a profile's [`KeyGuardSettings`]: acceptance time,
+//! activate on release, ignore repeat and first touch only.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::{InputEvent, KeyGuard};
+//! use lovewords_core::storage::KeyGuardSettings;
+//!
+//! let mut guard = KeyGuard::new(KeyGuardSettings {
+//!     ignore_repeat: Duration::from_millis(500),
+//!     ..KeyGuardSettings::default()
+//! });
+//!
+//! let tap = InputEvent::tap(0, 0);
+//! assert_eq!(guard.filter(tap.clone(), Duration::ZERO), vec![tap.clone()]);
+//! assert!(guard.filter(tap, Duration::from_millis(200)).is_empty());
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::storage::KeyGuardSettings;
+
+use super::event::InputEvent;
+
+/// Which selections count as repeats of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    /// Touch, pointer and dwell selections.
+    Pointer,
+    /// A single switch.
+    Switch(u8),
+}
+
+/// A touch being held on the grid.
+#[derive(Debug, Clone, Copy)]
+struct Touch {
+    id: u32,
+    cell: Option<(usize, usize)>,
+    since: Duration,
+    fired: bool,
+}
+
+/// What happened to a held switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HoldState {
+    /// Not yet accepted.
+    Pending,
+    /// The press was passed on, so its release will be too.
+    Accepted,
+    /// The press was dropped as a repeat.
+    Rejected,
+}
+
+/// A switch being held.
+#[derive(Debug, Clone, Copy)]
+struct SwitchHold {
+    since: Duration,
+    state: HoldState,
+}
+
+/// Input filter that drops accidental touches and switch presses.
+///
+/// Events that already describe a complete selection (taps, dwells, long
+/// presses) go through [`filter`](Self::filter), which can only apply the
+/// ignore-repeat rule. Clients that can report raw touches should use
+/// [`touch_down`](Self::touch_down), [`touch_move`](Self::touch_move) and
+/// [`touch_up`](Self::touch_up) so every rule applies. Held touches and
+/// switches are accepted by [`update`](Self::update) once the acceptance
+/// time has passed, so call it regularly.
+#[derive(Debug, Clone, Default)]
+pub struct KeyGuard {
+    settings: KeyGuardSettings,
+    touch: Option<Touch>,
+    switches: BTreeMap<u8, SwitchHold>,
+    pressed: BTreeMap<u8, Duration>,
+    last_selected: BTreeMap<Source, Duration>,
+}
+
+impl KeyGuard {
+    /// Create a filter with the given rules.
+    pub fn new(settings: KeyGuardSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Get the rules.
+    pub fn settings(&self) -> &KeyGuardSettings {
+        &self.settings
+    }
+
+    /// Replace the rules.
+    pub fn set_settings(&mut self, settings: KeyGuardSettings) {
+        self.settings = settings;
+    }
+
+    /// Forget held touches and switches and past selections.
+    pub fn reset(&mut self) {
+        self.touch = None;
+        self.switches.clear();
+        self.pressed.clear();
+        self.last_selected.clear();
+    }
+
+    /// Get when a switch last went down, before any acceptance time.
+    ///
+    /// A press held back by the guard is passed on late, so consumers that
+    /// time holds, like Morse code, should measure from here.
+    pub fn press_time(&self, switch_id: u8) -> Option<Duration> {
+        self.pressed.get(&switch_id).copied()
+    }
+
+    /// Filter an input event received at `now`.
+    ///
+    /// Returns the events to pass on, which may be none, or a held-back
+    /// switch press together with its release.
+    pub fn filter(&mut self, event: InputEvent, now: Duration) -> Vec<InputEvent> {
+        match event {
+            InputEvent::Tap { .. }
+            | InputEvent::DoubleTap { .. }
+            | InputEvent::LongPress { .. }
+            | InputEvent::Dwell { .. } => self
+                .select(Source::Pointer, event, now)
+                .into_iter()
+                .collect(),
+            InputEvent::SwitchPress { switch_id } => self.switch_down(switch_id, now),
+            InputEvent::SwitchRelease { switch_id } => self.switch_up(switch_id, now),
+            InputEvent::Key(_) | InputEvent::Scroll { .. } | InputEvent::Voice { .. } => {
+                vec![event]
+            }
+        }
+    }
+
+    /// Handle a finger or pointer touching a cell (or `None` for no cell).
+    pub fn touch_down(
+        &mut self,
+        id: u32,
+        cell: Option<(usize, usize)>,
+        now: Duration,
+    ) -> Vec<InputEvent> {
+        if self.touch.is_some() && self.settings.first_touch_only {
+            return Vec::new();
+        }
+        self.touch = Some(Touch {
+            id,
+            cell,
+            since: now,
+            fired: false,
+        });
+        self.update(now)
+    }
+
+    /// Handle a touch sliding onto another cell.
+    ///
+    /// The acceptance time restarts on the new cell. With first touch only,
+    /// the cell first touched is kept.
+    pub fn touch_move(
+        &mut self,
+        id: u32,
+        cell: Option<(usize, usize)>,
+        now: Duration,
+    ) -> Vec<InputEvent> {
+        if !self.settings.first_touch_only {
+            if let Some(touch) = self.touch.as_mut().filter(|t| t.id == id && t.cell != cell) {
+                touch.cell = cell;
+                touch.since = now;
+            }
+        }
+        self.update(now)
+    }
+
+    /// Handle a touch being lifted.
+    pub fn touch_up(&mut self, id: u32, now: Duration) -> Vec<InputEvent> {
+        let mut events = self.update(now);
+        let Some(touch) = self.touch.filter(|t| t.id == id) else {
+            return events;
+        };
+        self.touch = None;
+
+        if self.settings.activate_on_release && !touch.fired && self.is_accepted(touch.since, now) {
+            if let Some((row, col)) = touch.cell {
+                events.extend(self.select(Source::Pointer, InputEvent::tap(row, col), now));
+            }
+        }
+        events
+    }
+
+    /// Accept held touches and switches whose acceptance time has passed.
+    pub fn update(&mut self, now: Duration) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        if self.settings.activate_on_release {
+            return events;
+        }
+
+        if let Some(touch) = self
+            .touch
+            .filter(|t| !t.fired && self.is_accepted(t.since, now))
+        {
+            if let Some((row, col)) = touch.cell {
+                events.extend(self.select(Source::Pointer, InputEvent::tap(row, col), now));
+                self.touch = Some(Touch {
+                    fired: true,
+                    ..touch
+                });
+            }
+        }
+
+        let due: Vec<u8> = self
+            .switches
+            .iter()
+            .filter(|(_, hold)| {
+                hold.state == HoldState::Pending && self.is_accepted(hold.since, now)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for switch_id in due {
+            let accepted = self.select(
+                Source::Switch(switch_id),
+                InputEvent::SwitchPress { switch_id },
+                now,
+            );
+            self.set_switch_state(switch_id, accepted.is_some());
+            events.extend(accepted);
+        }
+        events
+    }
+
+    fn switch_down(&mut self, switch_id: u8, now: Duration) -> Vec<InputEvent> {
+        // Some switch interfaces never report releases, so a new press
+        // simply replaces any earlier one
+        self.pressed.insert(switch_id, now);
+        self.switches.insert(
+            switch_id,
+            SwitchHold {
+                since: now,
+                state: HoldState::Pending,
+            },
+        );
+        self.update(now)
+    }
+
+    fn switch_up(&mut self, switch_id: u8, now: Duration) -> Vec<InputEvent> {
+        let mut events = self.update(now);
+        let Some(hold) = self.switches.remove(&switch_id) else {
+            return events;
+        };
+        let release = InputEvent::SwitchRelease { switch_id };
+        match hold.state {
+            HoldState::Accepted => events.push(release),
+            HoldState::Pending
+                if self.settings.activate_on_release && self.is_accepted(hold.since, now) =>
+            {
+                let press = InputEvent::SwitchPress { switch_id };
+                if let Some(press) = self.select(Source::Switch(switch_id), press, now) {
+                    events.push(press);
+                    events.push(release);
+                }
+            }
+            HoldState::Pending | HoldState::Rejected => {}
+        }
+        events
+    }
+
+    fn set_switch_state(&mut self, switch_id: u8, accepted: bool) {
+        if let Some(hold) = self.switches.get_mut(&switch_id) {
+            hold.state = if accepted {
+                HoldState::Accepted
+            } else {
+                HoldState::Rejected
+            };
+        }
+    }
+
+    fn is_accepted(&self, since: Duration, now: Duration) -> bool {
+        now.saturating_sub(since) >= self.settings.acceptance_time
+    }
+
+    fn select(&mut self, source: Source, event: InputEvent, now: Duration) -> Option<InputEvent> {
+        let repeat = self
+            .last_selected
+            .get(&source)
+            .is_some_and(|last| now.saturating_sub(*last) < self.settings.ignore_repeat);
+        if repeat {
+            return None;
+        }
+        self.last_selected.insert(source, now);
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn guard(settings: KeyGuardSettings) -> KeyGuard {
+        KeyGuard::new(settings)
+    }
+
+    #[test]
+    fn test_defaults_pass_everything() {
+        let mut guard = guard(KeyGuardSettings::default());
+        assert_eq!(
+            guard.touch_down(0, Some((1, 2)), ms(0)),
+            vec![InputEvent::tap(1, 2)]
+        );
+        assert!(guard.touch_up(0, ms(10)).is_empty());
+
+        assert_eq!(
+            guard.filter(InputEvent::switch_press(), ms(20)),
+            vec![InputEvent::switch_press()]
+        );
+        assert_eq!(
+            guard.filter(InputEvent::SwitchRelease { switch_id: 0 }, ms(30)),
+            vec![InputEvent::SwitchRelease { switch_id: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_acceptance_time() {
+        let mut guard = guard(KeyGuardSettings {
+            acceptance_time: ms(300),
+            ..KeyGuardSettings::default()
+        });
+
+        // A brief brush is ignored
+        assert!(guard.touch_down(0, Some((0, 0)), ms(0)).is_empty());
+        assert!(guard.touch_up(0, ms(100)).is_empty());
+
+        // Holding long enough selects, once
+        guard.touch_down(0, Some((0, 0)), ms(1000));
+        assert_eq!(guard.update(ms(1300)), vec![InputEvent::tap(0, 0)]);
+        assert!(guard.update(ms(1600)).is_empty());
+        assert!(guard.touch_up(0, ms(1700)).is_empty());
+
+        // Sliding onto another cell restarts the timer
+        guard.touch_down(0, Some((0, 0)), ms(2000));
+        guard.touch_move(0, Some((0, 1)), ms(2200));
+        assert!(guard.update(ms(2400)).is_empty());
+        assert_eq!(guard.update(ms(2500)), vec![InputEvent::tap(0, 1)]);
+
+        // Switch presses are held back, and short ones dropped with their release
+        assert!(guard
+            .filter(InputEvent::switch_press(), ms(3000))
+            .is_empty());
+        assert!(guard
+            .filter(InputEvent::SwitchRelease { switch_id: 0 }, ms(3100))
+            .is_empty());
+        guard.filter(InputEvent::switch_press(), ms(4000));
+        assert_eq!(guard.update(ms(4300)), vec![InputEvent::switch_press()]);
+    }
+
+    #[test]
+    fn test_activate_on_release() {
+        let mut guard = guard(KeyGuardSettings {
+            activate_on_release: true,
+            ..KeyGuardSettings::default()
+        });
+
+        assert!(guard.touch_down(0, Some((0, 0)), ms(0)).is_empty());
+        guard.touch_move(0, Some((1, 1)), ms(100));
+        assert_eq!(guard.touch_up(0, ms(200)), vec![InputEvent::tap(1, 1)]);
+
+        // Lifting outside the grid cancels
+        guard.touch_down(0, Some((0, 0)), ms(300));
+        guard.touch_move(0, None, ms(400));
+        assert!(guard.touch_up(0, ms(500)).is_empty());
+
+        assert!(guard.filter(InputEvent::switch_press(), ms(600)).is_empty());
+        assert_eq!(
+            guard.filter(InputEvent::SwitchRelease { switch_id: 0 }, ms(700)),
+            vec![
+                InputEvent::switch_press(),
+                InputEvent::SwitchRelease { switch_id: 0 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_repeat() {
+        let mut guard = guard(KeyGuardSettings {
+            ignore_repeat: ms(500),
+            ..KeyGuardSettings::default()
+        });
+
+        assert_eq!(guard.filter(InputEvent::tap(0, 0), ms(0)).len(), 1);
+        assert!(guard.filter(InputEvent::tap(0, 1), ms(300)).is_empty());
+        assert!(guard
+            .filter(InputEvent::dwell(0, 1, ms(1000)), ms(400))
+            .is_empty());
+        assert_eq!(guard.filter(InputEvent::tap(0, 1), ms(500)).len(), 1);
+
+        // Switches are tracked separately from touches and from each other
+        assert_eq!(guard.filter(InputEvent::switch_press(), ms(600)).len(), 1);
+        guard.filter(InputEvent::SwitchRelease { switch_id: 0 }, ms(650));
+        assert_eq!(
+            guard.filter(InputEvent::switch_secondary(), ms(700)).len(),
+            1
+        );
+
+        // A dropped press also drops its release
+        assert!(guard.filter(InputEvent::switch_press(), ms(800)).is_empty());
+        assert!(guard
+            .filter(InputEvent::SwitchRelease { switch_id: 0 }, ms(850))
+            .is_empty());
+
+        // Other events always pass
+        assert_eq!(
+            guard
+                .filter(
+                    InputEvent::Voice {
+                        command: "yes".into()
+                    },
+                    ms(900)
+                )
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_first_touch_only() {
+        let mut guard = guard(KeyGuardSettings {
+            first_touch_only: true,
+            activate_on_release: true,
+            ..KeyGuardSettings::default()
+        });
+
+        guard.touch_down(0, Some((0, 0)), ms(0));
+        assert!(guard.touch_down(1, Some((1, 1)), ms(50)).is_empty());
+        guard.touch_move(0, Some((0, 1)), ms(100));
+
+        // The second finger lifting does nothing; the first selects where it landed
+        assert!(guard.touch_up(1, ms(150)).is_empty());
+        assert_eq!(guard.touch_up(0, ms(200)), vec![InputEvent::tap(0, 0)]);
+    }
+}
@@ -0,0 +1,433 @@
+//! Keyboard navigation over a board.
+//!
+//! [`KeyboardNavigator`] applies [`KeyEvent`]s to a [`BoardNavigator`]'s
+//! cursor. It skips empty and hidden cells, wraps or clamps at the edges,
+//! and can move focus out of the grid into a message bar above it and a
+//! toolbar below it.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lovewords_core::input::{Focus, KeyEvent, KeyOutcome, KeyboardNavigator};
+//! use lovewords_core::{BoardNavigator, ObfBoard, ObfButton};
+//!
+//! let mut board = ObfBoard::new("home", 1, 3);
+//! board.add_button(ObfButton::speak("a", "A"));
+//! board.add_button(ObfButton::speak("c", "C"));
+//! board.place_button_at("a", 0, 0);
+//! board.place_button_at("c", 0, 2);
+//!
+//! let mut nav = BoardNavigator::new(board);
+//! let mut keys = KeyboardNavigator::default();
+//!
+//! keys.handle(KeyEvent::Right, &mut nav);
+//! // The empty middle cell is skipped
+//! assert_eq!(
+//!     keys.handle(KeyEvent::Right, &mut nav),
+//!     KeyOutcome::Moved(Focus::Cell { row: 0, col: 2 })
+//! );
+//! ```
+
+use crate::board::{BoardNavigator, CellAction};
+
+use super::event::KeyEvent;
+
+/// What happens when the cursor reaches the edge of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeBehavior {
+    /// Stay on the last item.
+    #[default]
+    Clamp,
+    /// Continue from the opposite edge.
+    Wrap,
+}
+
+/// Keyboard navigation options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardConfig {
+    /// Skip empty positions and hidden buttons.
+    pub skip_empty: bool,
+
+    /// What happens at the edges.
+    pub edges: EdgeBehavior,
+
+    /// Whether the message bar above the grid can take focus.
+    pub message_bar: bool,
+
+    /// Actions of the toolbar below the grid, left to right.
+    pub toolbar: Vec<CellAction>,
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            skip_empty: true,
+            edges: EdgeBehavior::Clamp,
+            message_bar: false,
+            toolbar: Vec::new(),
+        }
+    }
+}
+
+/// Where keyboard focus is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    /// A cell of the board.
+    Cell { row: usize, col: usize },
+    /// The message bar.
+    MessageBar,
+    /// A toolbar item, by index.
+    Toolbar(usize),
+}
+
+/// Result of handling a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOutcome {
+    /// Focus moved.
+    Moved(Focus),
+    /// The key had no effect (e.g. clamped at an edge).
+    Unchanged,
+    /// The focused item should be activated.
+    Activate(Focus),
+    /// The user asked to go back.
+    Back,
+}
+
+/// A row of focus stops, from top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    MessageBar,
+    Row(usize),
+    Toolbar,
+}
+
+/// Applies keyboard events to a board navigator.
+///
+/// Focus on the grid is the navigator's cursor; focus on the message bar
+/// or toolbar is tracked here, and the cursor is cleared meanwhile.
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardNavigator {
+    config: KeyboardConfig,
+    region: Option<Focus>,
+    /// Column to return to when moving vertically.
+    column: usize,
+}
+
+impl KeyboardNavigator {
+    /// Create a keyboard navigator with the given options.
+    pub fn new(config: KeyboardConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Get the options.
+    pub fn config(&self) -> &KeyboardConfig {
+        &self.config
+    }
+
+    /// Get the current focus.
+    pub fn focus(&self, nav: &BoardNavigator) -> Option<Focus> {
+        self.region
+            .or_else(|| nav.cursor().map(|(row, col)| Focus::Cell { row, col }))
+    }
+
+    /// Move focus to an item.
+    pub fn set_focus(&mut self, focus: Focus, nav: &mut BoardNavigator) {
+        match focus {
+            Focus::Cell { row, col } => {
+                self.region = None;
+                self.column = col;
+                nav.set_cursor(row, col);
+            }
+            Focus::MessageBar | Focus::Toolbar(_) => {
+                self.region = Some(focus);
+                nav.clear_cursor();
+            }
+        }
+    }
+
+    /// Apply a key to the navigator.
+    pub fn handle(&mut self, key: KeyEvent, nav: &mut BoardNavigator) -> KeyOutcome {
+        let current = self.focus(nav);
+        if let Some(Focus::Cell { col, .. }) = current {
+            self.column = col;
+        }
+        let target = match key {
+            KeyEvent::Enter | KeyEvent::Space => {
+                return current.map_or(KeyOutcome::Unchanged, KeyOutcome::Activate);
+            }
+            KeyEvent::Escape => return KeyOutcome::Back,
+            _ if current.is_none() => match key {
+                KeyEvent::ShiftTab => self.stops(nav).last().copied(),
+                _ => self
+                    .first_cell(nav)
+                    .or_else(|| self.stops(nav).first().copied()),
+            },
+            KeyEvent::Up => self.vertical(current, false, nav),
+            KeyEvent::Down => self.vertical(current, true, nav),
+            KeyEvent::Left => self.horizontal(current, false, nav),
+            KeyEvent::Right => self.horizontal(current, true, nav),
+            KeyEvent::Tab => self.tab(current, true, nav),
+            KeyEvent::ShiftTab => self.tab(current, false, nav),
+        };
+
+        match target {
+            Some(focus) if Some(focus) != current => {
+                self.set_focus(focus, nav);
+                KeyOutcome::Moved(focus)
+            }
+            _ => KeyOutcome::Unchanged,
+        }
+    }
+
+    /// Forget focus outside the grid.
+    pub fn reset(&mut self) {
+        self.region = None;
+        self.column = 0;
+    }
+
+    fn usable(&self, nav: &BoardNavigator) -> Vec<Vec<bool>> {
+        let board = nav.current();
+        if self.config.skip_empty {
+            board.occupancy()
+        } else {
+            vec![vec![true; board.grid.columns]; board.grid.rows]
+        }
+    }
+
+    fn first_cell(&self, nav: &BoardNavigator) -> Option<Focus> {
+        self.stops(nav)
+            .into_iter()
+            .find(|f| matches!(f, Focus::Cell { .. }))
+    }
+
+    /// Every focus stop in reading order.
+    fn stops(&self, nav: &BoardNavigator) -> Vec<Focus> {
+        let mut stops = Vec::new();
+        if self.config.message_bar {
+            stops.push(Focus::MessageBar);
+        }
+        for (row, cols) in self.usable(nav).iter().enumerate() {
+            for (col, &usable) in cols.iter().enumerate() {
+                if usable {
+                    stops.push(Focus::Cell { row, col });
+                }
+            }
+        }
+        stops.extend((0..self.config.toolbar.len()).map(Focus::Toolbar));
+        stops
+    }
+
+    /// Rows of focus stops from top to bottom.
+    fn lines(&self, usable: &[Vec<bool>]) -> Vec<Line> {
+        let mut lines = Vec::new();
+        if self.config.message_bar {
+            lines.push(Line::MessageBar);
+        }
+        lines.extend(
+            usable
+                .iter()
+                .enumerate()
+                .filter(|(_, cols)| cols.contains(&true))
+                .map(|(row, _)| Line::Row(row)),
+        );
+        if !self.config.toolbar.is_empty() {
+            lines.push(Line::Toolbar);
+        }
+        lines
+    }
+
+    fn tab(&self, current: Option<Focus>, forward: bool, nav: &BoardNavigator) -> Option<Focus> {
+        let stops = self.stops(nav);
+        let index = stops.iter().position(|s| Some(*s) == current);
+        let next = match index {
+            Some(i) => self.step(i, stops.len(), forward)?,
+            None => 0,
+        };
+        stops.get(next).copied()
+    }
+
+    fn vertical(&self, current: Option<Focus>, down: bool, nav: &BoardNavigator) -> Option<Focus> {
+        let usable = self.usable(nav);
+        let lines = self.lines(&usable);
+        let line = match current? {
+            Focus::MessageBar => Line::MessageBar,
+            Focus::Toolbar(_) => Line::Toolbar,
+            Focus::Cell { row, .. } => Line::Row(row),
+        };
+        let next = match lines.iter().position(|l| *l == line) {
+            Some(i) => lines[self.step(i, lines.len(), down)?],
+            // The cursor is on a row without usable cells; take the nearest
+            None => {
+                let Line::Row(row) = line else {
+                    return None;
+                };
+                let mut rows = lines
+                    .iter()
+                    .filter(|l| matches!(l, Line::Row(r) if (*r > row) == down));
+                *if down { rows.next() } else { rows.next_back() }?
+            }
+        };
+
+        Some(match next {
+            Line::MessageBar => Focus::MessageBar,
+            Line::Toolbar => Focus::Toolbar(self.column.min(self.config.toolbar.len() - 1)),
+            Line::Row(row) => {
+                let col = (0..usable[row].len())
+                    .filter(|&c| usable[row][c])
+                    .min_by_key(|&c| c.abs_diff(self.column))?;
+                Focus::Cell { row, col }
+            }
+        })
+    }
+
+    fn horizontal(
+        &self,
+        current: Option<Focus>,
+        right: bool,
+        nav: &BoardNavigator,
+    ) -> Option<Focus> {
+        match current? {
+            Focus::MessageBar => None,
+            Focus::Toolbar(i) => self
+                .step(i, self.config.toolbar.len(), right)
+                .map(Focus::Toolbar),
+            Focus::Cell { row, col } => {
+                let cols = self.usable(nav).into_iter().nth(row)?;
+                let mut next = col;
+                for _ in 1..cols.len() {
+                    next = self.step(next, cols.len(), right)?;
+                    if cols[next] {
+                        return Some(Focus::Cell { row, col: next });
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Move one position within `0..len`, wrapping or clamping at the ends.
+    fn step(&self, index: usize, len: usize, forward: bool) -> Option<usize> {
+        match (forward, self.config.edges) {
+            (true, _) if index + 1 < len => Some(index + 1),
+            (false, _) if index > 0 => Some(index - 1),
+            (_, EdgeBehavior::Clamp) => None,
+            (true, EdgeBehavior::Wrap) => Some(0),
+            (false, EdgeBehavior::Wrap) => len.checked_sub(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obf::{ObfBoard, ObfButton};
+
+    /// ```text
+    /// a . c
+    /// . . .
+    /// g h (i hidden)
+    /// ```
+    fn nav() -> BoardNavigator {
+        let mut board = ObfBoard::new("home", 3, 3);
+        for (id, row, col) in [("a", 0, 0), ("c", 0, 2), ("g", 2, 0), ("h", 2, 1)] {
+            board.add_button(ObfButton::speak(id, id));
+            board.place_button_at(id, row, col);
+        }
+        board.add_button(ObfButton {
+            hidden: true,
+            ..ObfButton::speak("i", "i")
+        });
+        board.place_button_at("i", 2, 2);
+        BoardNavigator::new(board)
+    }
+
+    fn cell(row: usize, col: usize) -> KeyOutcome {
+        KeyOutcome::Moved(Focus::Cell { row, col })
+    }
+
+    #[test]
+    fn test_skips_empty_and_hidden_cells() {
+        let mut nav = nav();
+        let mut keys = KeyboardNavigator::default();
+
+        assert_eq!(keys.handle(KeyEvent::Down, &mut nav), cell(0, 0));
+        assert_eq!(keys.handle(KeyEvent::Right, &mut nav), cell(0, 2));
+
+        // The empty row is skipped; the nearest usable column is picked
+        assert_eq!(keys.handle(KeyEvent::Down, &mut nav), cell(2, 1));
+        assert_eq!(
+            keys.handle(KeyEvent::Right, &mut nav),
+            KeyOutcome::Unchanged
+        );
+
+        assert_eq!(keys.handle(KeyEvent::Tab, &mut nav), KeyOutcome::Unchanged);
+        assert_eq!(keys.handle(KeyEvent::ShiftTab, &mut nav), cell(2, 0));
+        assert_eq!(nav.cursor(), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_wrap_and_clamp() {
+        let mut nav = nav();
+        let mut keys = KeyboardNavigator::default();
+        nav.set_cursor(0, 0);
+        assert_eq!(keys.handle(KeyEvent::Up, &mut nav), KeyOutcome::Unchanged);
+        assert_eq!(keys.handle(KeyEvent::Left, &mut nav), KeyOutcome::Unchanged);
+
+        let mut keys = KeyboardNavigator::new(KeyboardConfig {
+            edges: EdgeBehavior::Wrap,
+            ..KeyboardConfig::default()
+        });
+        assert_eq!(keys.handle(KeyEvent::Up, &mut nav), cell(2, 0));
+        assert_eq!(keys.handle(KeyEvent::Left, &mut nav), cell(2, 1));
+        assert_eq!(keys.handle(KeyEvent::Tab, &mut nav), cell(0, 0));
+
+        // Without skipping, every position is a stop
+        let mut keys = KeyboardNavigator::new(KeyboardConfig {
+            skip_empty: false,
+            ..KeyboardConfig::default()
+        });
+        assert_eq!(keys.handle(KeyEvent::Right, &mut nav), cell(0, 1));
+        assert_eq!(keys.handle(KeyEvent::Down, &mut nav), cell(1, 1));
+    }
+
+    #[test]
+    fn test_message_bar_and_toolbar() {
+        let mut nav = nav();
+        let mut keys = KeyboardNavigator::new(KeyboardConfig {
+            message_bar: true,
+            toolbar: vec![CellAction::Back, CellAction::Home, CellAction::Clear],
+            ..KeyboardConfig::default()
+        });
+
+        nav.set_cursor(0, 2);
+        assert_eq!(
+            keys.handle(KeyEvent::Up, &mut nav),
+            KeyOutcome::Moved(Focus::MessageBar)
+        );
+        assert_eq!(nav.cursor(), None);
+        assert_eq!(
+            keys.handle(KeyEvent::Enter, &mut nav),
+            KeyOutcome::Activate(Focus::MessageBar)
+        );
+
+        // Back down to the column we came from
+        assert_eq!(keys.handle(KeyEvent::Down, &mut nav), cell(0, 2));
+        keys.handle(KeyEvent::Down, &mut nav);
+        assert_eq!(
+            keys.handle(KeyEvent::Down, &mut nav),
+            KeyOutcome::Moved(Focus::Toolbar(1))
+        );
+        assert_eq!(
+            keys.handle(KeyEvent::Right, &mut nav),
+            KeyOutcome::Moved(Focus::Toolbar(2))
+        );
+        assert_eq!(
+            keys.handle(KeyEvent::Space, &mut nav),
+            KeyOutcome::Activate(Focus::Toolbar(2))
+        );
+        assert_eq!(keys.handle(KeyEvent::Escape, &mut nav), KeyOutcome::Back);
+    }
+}
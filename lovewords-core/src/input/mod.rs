@@ -1,12 +1,49 @@
 //! Input handling for various interaction methods.
 //!
 //! This module provides abstractions for different input methods:
-//! - Direct touch/click
-//! - Switch scanning (row/column, linear)
-//! - Dwell selection (hover to select)
+//! - Direct touch/click, with tap, double-tap, long-press and swipe gestures
+//! - Switch scanning (row/column, linear, block), with timed auto-scan and
+//!   a scan rate tuned from the user's reaction times
+//! - Morse code with one or two switches
+//! - Dwell selection (hover to select), from pointer or gaze coordinates
+//! - Eye-gaze fixation filtering with blink handling and target snapping
+//! - Filtering of accidental activations (key guard)
+//! - Keyboard navigation that skips empty cells and reaches the toolbar
+//! - Configurable bindings from physical keys and buttons to switches
+//! - Routing between input methods, resolving conflicts between them
+//! - Voice commands, with fuzzy matching of labels and board names
 
+mod binding;
+mod clock;
+mod dwell;
 mod event;
+mod gaze;
+mod geometry;
+mod gesture;
+mod key_guard;
+mod keyboard;
+mod morse;
+mod router;
+mod scan_driver;
+mod scan_rate;
 mod scanning;
+mod voice;
 
-pub use event::InputEvent;
-pub use scanning::{ScanMode, ScanState, Scanner};
+pub use binding::{Binding, BindingMap, BindingTarget, PhysicalInput};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use dwell::{DwellConfig, DwellTracker, DwellUpdate};
+pub use event::{InputEvent, KeyEvent};
+pub use gaze::{Fixation, GazeConfig, GazeFilter, GazeSample, GazeUpdate};
+pub use geometry::{GridGeometry, Rect};
+pub use gesture::GestureRecognizer;
+pub use key_guard::KeyGuard;
+pub use keyboard::{EdgeBehavior, Focus, KeyOutcome, KeyboardConfig, KeyboardNavigator};
+pub use morse::{MorseCommand, MorseDecoder, MorseOutput};
+pub use router::{InputMethod, InputRouter, RouterConfig};
+pub use scan_driver::{ScanDriver, SwitchOutcome};
+pub use scan_rate::ScanRateTuner;
+pub use scanning::{
+    ScanAnnouncement, ScanBlock, ScanConfig, ScanLevel, ScanMode, ScanOrder, ScanState,
+    ScanTechnique, Scanner,
+};
+pub use voice::{similarity, VoiceCommand, VoiceGrammar, DEFAULT_VOICE_THRESHOLD};
@@ -0,0 +1,359 @@
+//! Morse code input from one or two switches.
+//!
+//! [`MorseDecoder`] turns switch timing into letters and editing commands.
+//! With one switch, short presses are dots and long presses are dashes;
+//! with two switches, the primary switch enters a dot and the secondary a
+//! dash. A letter ends when the switches stay idle for three units.
+//!
+//! Besides the International Morse alphabet, a few codes are reserved for
+//! editing the message:
+//!
+//! | Code       | Command                     |
+//! |------------|-----------------------------|
+//! | `..--`     | [`MorseCommand::Space`]     |
+//! | `----`     | [`MorseCommand::Backspace`] |
+//! | `.-.-`     | [`MorseCommand::Speak`]     |
+//! | `........` | [`MorseCommand::Clear`]     |
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::{MorseDecoder, MorseOutput};
+//! use lovewords_core::storage::MorseSettings;
+//!
+//! let ms = Duration::from_millis;
+//! let mut morse = MorseDecoder::new(MorseSettings::default());
+//!
+//! // A short press then a long press: ".-"
+//! morse.on_switch_down(0, ms(0));
+//! morse.on_switch_up(0, ms(150));
+//! morse.on_switch_down(0, ms(350));
+//! morse.on_switch_up(0, ms(950));
+//! assert_eq!(morse.code(), ".-");
+//!
+//! assert_eq!(morse.update(ms(1600)), Some(MorseOutput::Char('a')));
+//! ```
+
+use std::time::Duration;
+
+use crate::storage::{MorseMode, MorseSettings};
+
+/// International Morse code, plus the editing commands.
+const CODES: &[(&str, char)] = &[
+    (".-", 'a'),
+    ("-...", 'b'),
+    ("-.-.", 'c'),
+    ("-..", 'd'),
+    (".", 'e'),
+    ("..-.", 'f'),
+    ("--.", 'g'),
+    ("....", 'h'),
+    ("..", 'i'),
+    (".---", 'j'),
+    ("-.-", 'k'),
+    (".-..", 'l'),
+    ("--", 'm'),
+    ("-.", 'n'),
+    ("---", 'o'),
+    (".--.", 'p'),
+    ("--.-", 'q'),
+    (".-.", 'r'),
+    ("...", 's'),
+    ("-", 't'),
+    ("..-", 'u'),
+    ("...-", 'v'),
+    (".--", 'w'),
+    ("-..-", 'x'),
+    ("-.--", 'y'),
+    ("--..", 'z'),
+    ("-----", '0'),
+    (".----", '1'),
+    ("..---", '2'),
+    ("...--", '3'),
+    ("....-", '4'),
+    (".....", '5'),
+    ("-....", '6'),
+    ("--...", '7'),
+    ("---..", '8'),
+    ("----.", '9'),
+    (".-.-.-", '.'),
+    ("--..--", ','),
+    ("..--..", '?'),
+    (".----.", '\''),
+    ("-.-.--", '!'),
+    ("-..-.", '/'),
+    ("---...", ':'),
+    ("-.-.-.", ';'),
+    ("-...-", '='),
+    (".-.-.", '+'),
+    ("-....-", '-'),
+    (".-..-.", '"'),
+    (".--.-.", '@'),
+];
+
+/// An editing command entered in Morse code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorseCommand {
+    /// Finish the current word.
+    Space,
+    /// Remove the last letter.
+    Backspace,
+    /// Speak the message.
+    Speak,
+    /// Clear the message.
+    Clear,
+}
+
+impl MorseCommand {
+    /// Get the command for a code, if it is one.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "..--" => Some(Self::Space),
+            "----" => Some(Self::Backspace),
+            ".-.-" => Some(Self::Speak),
+            "........" => Some(Self::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded Morse letter or command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MorseOutput {
+    /// A letter, digit or punctuation mark.
+    Char(char),
+    /// An editing command.
+    Command(MorseCommand),
+    /// A code that means nothing.
+    Unknown(String),
+}
+
+impl MorseOutput {
+    /// Decode a sequence of dots and dashes.
+    pub fn decode(code: &str) -> Self {
+        if let Some(command) = MorseCommand::from_code(code) {
+            return Self::Command(command);
+        }
+        CODES.iter().find(|(c, _)| *c == code).map_or_else(
+            || Self::Unknown(code.to_string()),
+            |(_, ch)| Self::Char(*ch),
+        )
+    }
+}
+
+/// Weight of each new timing sample when adapting the unit.
+const ADAPT_RATE: f32 = 0.2;
+
+/// Decodes switch timing into Morse letters and commands.
+///
+/// The decoder only sees switch ids and times; feed it from
+/// [`InputEvent::SwitchPress`] and [`InputEvent::SwitchRelease`] and call
+/// [`update`](Self::update) regularly to finish letters after a pause.
+///
+/// With adaptive timing the unit follows the user's dots, dashes and gaps,
+/// within a third and three times the configured unit.
+///
+/// [`InputEvent::SwitchPress`]: super::InputEvent::SwitchPress
+/// [`InputEvent::SwitchRelease`]: super::InputEvent::SwitchRelease
+#[derive(Debug, Clone)]
+pub struct MorseDecoder {
+    settings: MorseSettings,
+    unit: Duration,
+    code: String,
+    pressed: Option<Duration>,
+    released: Option<Duration>,
+}
+
+impl MorseDecoder {
+    /// Create a decoder with the given settings.
+    pub fn new(settings: MorseSettings) -> Self {
+        Self {
+            unit: settings.unit,
+            settings,
+            code: String::new(),
+            pressed: None,
+            released: None,
+        }
+    }
+
+    /// Get the settings.
+    pub fn settings(&self) -> &MorseSettings {
+        &self.settings
+    }
+
+    /// Get the current unit (dot length), after adaptation.
+    pub fn unit(&self) -> Duration {
+        self.unit
+    }
+
+    /// Get the dots and dashes of the letter being entered.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Discard the letter being entered.
+    pub fn reset(&mut self) {
+        self.code.clear();
+        self.pressed = None;
+        self.released = None;
+    }
+
+    /// Handle a switch going down at `now`.
+    ///
+    /// Returns the previous letter if the pause before this press ended it.
+    pub fn on_switch_down(&mut self, switch_id: u8, now: Duration) -> Option<MorseOutput> {
+        let finished = self.update(now);
+        if let (false, Some(released)) = (self.code.is_empty(), self.released) {
+            self.adapt(now.saturating_sub(released));
+        }
+
+        match (self.settings.mode, switch_id) {
+            (MorseMode::SingleSwitch, 0) => self.pressed = Some(now),
+            (MorseMode::TwoSwitch, 0) => self.code.push('.'),
+            (MorseMode::TwoSwitch, 1) => self.code.push('-'),
+            _ => {}
+        }
+        finished
+    }
+
+    /// Handle a switch going up at `now`.
+    pub fn on_switch_up(&mut self, switch_id: u8, now: Duration) {
+        match (self.settings.mode, switch_id) {
+            (MorseMode::SingleSwitch, 0) => {
+                let Some(pressed) = self.pressed.take() else {
+                    return;
+                };
+                let held = now.saturating_sub(pressed);
+                if held < self.unit * 2 {
+                    self.code.push('.');
+                    self.adapt(held);
+                } else {
+                    self.code.push('-');
+                    self.adapt(held / 3);
+                }
+                self.released = Some(now);
+            }
+            (MorseMode::TwoSwitch, 0 | 1) => self.released = Some(now),
+            _ => {}
+        }
+    }
+
+    /// Finish the letter being entered if the switches have been idle for
+    /// three units.
+    pub fn update(&mut self, now: Duration) -> Option<MorseOutput> {
+        let idle = now.saturating_sub(self.released?);
+        if self.pressed.is_some() || self.code.is_empty() || idle < self.unit * 3 {
+            return None;
+        }
+        let output = MorseOutput::decode(&self.code);
+        self.reset();
+        Some(output)
+    }
+
+    fn adapt(&mut self, sample: Duration) {
+        if !self.settings.adaptive {
+            return;
+        }
+        let unit = self.unit.mul_f32(1.0 - ADAPT_RATE) + sample.mul_f32(ADAPT_RATE);
+        self.unit = unit.clamp(self.settings.unit / 3, self.settings.unit * 3);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Key a code on one switch with perfect timing, starting at `start`.
+    fn key(morse: &mut MorseDecoder, code: &str, start: u64, unit: u64) -> u64 {
+        let mut t = start;
+        for symbol in code.chars() {
+            let held = if symbol == '.' { unit } else { unit * 3 };
+            morse.on_switch_down(0, ms(t));
+            morse.on_switch_up(0, ms(t + held));
+            t += held + unit;
+        }
+        t
+    }
+
+    fn fixed() -> MorseSettings {
+        MorseSettings {
+            adaptive: false,
+            ..MorseSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_table() {
+        assert_eq!(
+            MorseOutput::decode("...---..."),
+            MorseOutput::Unknown("...---...".into())
+        );
+        assert_eq!(MorseOutput::decode("--.."), MorseOutput::Char('z'));
+        assert_eq!(MorseOutput::decode(".----"), MorseOutput::Char('1'));
+        assert_eq!(
+            MorseOutput::decode("..--"),
+            MorseOutput::Command(MorseCommand::Space)
+        );
+    }
+
+    #[test]
+    fn test_single_switch() {
+        let mut morse = MorseDecoder::new(fixed());
+        let end = key(&mut morse, "....", 0, 200);
+        assert_eq!(morse.code(), "....");
+
+        // The letter isn't finished until a three-unit pause
+        assert_eq!(morse.update(ms(end + 300)), None);
+        assert_eq!(morse.update(ms(end + 400)), Some(MorseOutput::Char('h')));
+        assert_eq!(morse.code(), "");
+
+        // A press after the pause also finishes the letter
+        let end = key(&mut morse, "..", 10_000, 200);
+        assert_eq!(
+            morse.on_switch_down(0, ms(end + 600)),
+            Some(MorseOutput::Char('i'))
+        );
+    }
+
+    #[test]
+    fn test_two_switch() {
+        let mut morse = MorseDecoder::new(MorseSettings {
+            mode: MorseMode::TwoSwitch,
+            ..fixed()
+        });
+
+        // Press length doesn't matter with two switches
+        for (switch_id, t) in [(0, 0), (1, 100), (0, 200), (1, 300)] {
+            morse.on_switch_down(switch_id, ms(t));
+            morse.on_switch_up(switch_id, ms(t + 50));
+        }
+        assert_eq!(
+            morse.update(ms(1000)),
+            Some(MorseOutput::Command(MorseCommand::Speak))
+        );
+    }
+
+    #[test]
+    fn test_adaptive_speed() {
+        let mut morse = MorseDecoder::new(MorseSettings::default());
+
+        // A slow user keys with a 400ms unit; the decoder follows
+        let mut t = 0;
+        for _ in 0..5 {
+            t = key(&mut morse, "-.-", t, 400);
+            t += 1200;
+            assert_eq!(morse.update(ms(t)), Some(MorseOutput::Char('k')));
+        }
+        assert!(morse.unit() > ms(300));
+
+        // Without adaptation a 400ms dot would read as a dash
+        let mut morse = MorseDecoder::new(fixed());
+        key(&mut morse, ".", 0, 400);
+        assert_eq!(morse.code(), "-");
+    }
+}
@@ -0,0 +1,358 @@
+//! Arbitration between input methods.
+//!
+//! A user may have touch, dwell, switch scanning and a keyboard active at
+//! once. Without arbitration, a tap made while the scan highlight is
+//! moving, or a dwell that fires while a switch is held, produces a second
+//! selection nobody intended. [`InputRouter`] receives every
+//! [`InputEvent`], drops events from methods the profile hasn't enabled,
+//! resolves conflicts between methods, and passes on a single coherent
+//! stream of selections:
+//!
+//! - while a switch is held, selections from other methods are dropped,
+//!   for at most [`RouterConfig::max_hold`]
+//! - after a selection, selections from a different method are dropped for
+//!   [`RouterConfig::conflict_window`]
+//! - a switch release is only passed on if its press was
+//! - a direct selection (touch, dwell, keyboard or voice) pauses switch
+//!   scanning for [`RouterConfig::scan_pause`]
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::{InputEvent, InputRouter, RouterConfig};
+//!
+//! let mut router = InputRouter::new(RouterConfig::default());
+//!
+//! // A dwell firing while the switch is held is dropped
+//! let press = InputEvent::switch_press();
+//! assert_eq!(router.route(press.clone(), Duration::ZERO), Some(press));
+//! let dwell = InputEvent::dwell(0, 0, Duration::from_secs(1));
+//! assert_eq!(router.route(dwell, Duration::from_millis(100)), None);
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::storage::ProfileSettings;
+
+use super::event::InputEvent;
+
+/// A family of input events that can be enabled or disabled together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InputMethod {
+    /// Taps, gestures and scrolling.
+    Touch,
+    /// Dwell selection from a pointer or eye tracker.
+    Dwell,
+    /// Switches, for scanning or Morse code.
+    Switch,
+    /// Keyboard navigation.
+    Keyboard,
+    /// Voice commands.
+    Voice,
+}
+
+impl InputMethod {
+    /// Get the method an event comes from.
+    pub fn of(event: &InputEvent) -> Self {
+        match event {
+            InputEvent::Tap { .. }
+            | InputEvent::DoubleTap { .. }
+            | InputEvent::LongPress { .. }
+            | InputEvent::Scroll { .. } => Self::Touch,
+            InputEvent::Dwell { .. } => Self::Dwell,
+            InputEvent::SwitchPress { .. } | InputEvent::SwitchRelease { .. } => Self::Switch,
+            InputEvent::Key(_) => Self::Keyboard,
+            InputEvent::Voice { .. } => Self::Voice,
+        }
+    }
+
+    /// Check if the method selects directly, without the scanner.
+    pub fn is_direct(&self) -> bool {
+        !matches!(self, Self::Switch)
+    }
+}
+
+/// Which input methods are enabled, and how conflicts are resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouterConfig {
+    /// Accept taps and gestures.
+    pub touch: bool,
+
+    /// Accept dwell selections.
+    pub dwell: bool,
+
+    /// Accept switch presses.
+    pub switch: bool,
+
+    /// Accept keyboard navigation.
+    pub keyboard: bool,
+
+    /// Accept voice commands.
+    pub voice: bool,
+
+    /// How long after a selection other methods can't select.
+    pub conflict_window: Duration,
+
+    /// How long a direct selection holds the scan highlight still.
+    pub scan_pause: Duration,
+
+    /// How long a held switch blocks other methods. Some switch interfaces
+    /// never report releases, so a press isn't trusted to last forever.
+    pub max_hold: Duration,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            touch: true,
+            dwell: true,
+            switch: true,
+            keyboard: true,
+            voice: true,
+            conflict_window: Duration::from_millis(500),
+            scan_pause: Duration::from_millis(2000),
+            max_hold: Duration::from_millis(3000),
+        }
+    }
+}
+
+impl RouterConfig {
+    /// Take method enablement from a profile.
+    ///
+    /// Switches are enabled for switch scanning or Morse code, and dwell
+    /// for dwell selection. Touch, keyboard and voice are always enabled.
+    pub fn from_settings(settings: &ProfileSettings) -> Self {
+        Self {
+            dwell: settings.accessibility.dwell_enabled,
+            switch: settings.accessibility.switch_scanning_enabled || settings.input.morse.enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Check if a method is enabled.
+    pub fn is_enabled(&self, method: InputMethod) -> bool {
+        match method {
+            InputMethod::Touch => self.touch,
+            InputMethod::Dwell => self.dwell,
+            InputMethod::Switch => self.switch,
+            InputMethod::Keyboard => self.keyboard,
+            InputMethod::Voice => self.voice,
+        }
+    }
+
+    /// Enable or disable a method.
+    pub fn set_enabled(&mut self, method: InputMethod, enabled: bool) {
+        let flag = match method {
+            InputMethod::Touch => &mut self.touch,
+            InputMethod::Dwell => &mut self.dwell,
+            InputMethod::Switch => &mut self.switch,
+            InputMethod::Keyboard => &mut self.keyboard,
+            InputMethod::Voice => &mut self.voice,
+        };
+        *flag = enabled;
+    }
+}
+
+/// Merges events from all input methods into one selection stream.
+#[derive(Debug, Clone, Default)]
+pub struct InputRouter {
+    config: RouterConfig,
+    held: BTreeMap<u8, Duration>,
+    last_selection: Option<(InputMethod, Duration)>,
+    scan_paused_until: Option<Duration>,
+}
+
+impl InputRouter {
+    /// Create a router with the given configuration.
+    pub fn new(config: RouterConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &RouterConfig {
+        &self.config
+    }
+
+    /// Replace the configuration.
+    ///
+    /// Switches held down by a method that is now disabled are forgotten.
+    pub fn set_config(&mut self, config: RouterConfig) {
+        if !config.switch {
+            self.held.clear();
+        }
+        self.config = config;
+    }
+
+    /// Forget held switches and past selections.
+    pub fn reset(&mut self) {
+        self.held.clear();
+        self.last_selection = None;
+        self.scan_paused_until = None;
+    }
+
+    /// Check if the scan highlight should stay still at `now` after a
+    /// direct selection.
+    pub fn is_scan_paused(&self, now: Duration) -> bool {
+        self.scan_paused_until.is_some_and(|until| now < until)
+    }
+
+    /// Get when scanning may resume, if a direct selection paused it.
+    pub fn scan_paused_until(&self) -> Option<Duration> {
+        self.scan_paused_until
+    }
+
+    /// Route an event received at `now`.
+    ///
+    /// Returns the event if it should be acted on.
+    pub fn route(&mut self, event: InputEvent, now: Duration) -> Option<InputEvent> {
+        let method = InputMethod::of(&event);
+        if !self.config.is_enabled(method) {
+            log::debug!("Dropped {:?}: {:?} input is disabled", event, method);
+            return None;
+        }
+
+        match event {
+            InputEvent::SwitchRelease { switch_id } => {
+                // Only pass releases whose press was passed on
+                return self.held.remove(&switch_id).is_some().then_some(event);
+            }
+            InputEvent::SwitchPress { switch_id } if self.is_contested(method, now) => {
+                log::debug!("Dropped press of switch {}: conflicting input", switch_id);
+                return None;
+            }
+            InputEvent::SwitchPress { switch_id } => {
+                // A new press replaces an earlier one that was never released
+                self.held.insert(switch_id, now);
+            }
+            _ if !is_selection(&event) => return Some(event),
+            _ if self.is_held(now) || self.is_contested(method, now) => {
+                log::debug!("Dropped {:?}: conflicting input", event);
+                return None;
+            }
+            _ => {}
+        }
+
+        self.last_selection = Some((method, now));
+        if method.is_direct() {
+            self.scan_paused_until = Some(now + self.config.scan_pause);
+        }
+        Some(event)
+    }
+
+    /// Check if a switch is held down, and not for longer than `max_hold`.
+    fn is_held(&self, now: Duration) -> bool {
+        self.held
+            .values()
+            .any(|since| now.saturating_sub(*since) < self.config.max_hold)
+    }
+
+    /// Check if another method made a selection too recently.
+    fn is_contested(&self, method: InputMethod, now: Duration) -> bool {
+        self.last_selection.is_some_and(|(last, at)| {
+            last != method && now.saturating_sub(at) < self.config.conflict_window
+        })
+    }
+}
+
+/// Check if an event chooses something, rather than moving focus.
+fn is_selection(event: &InputEvent) -> bool {
+    match event {
+        InputEvent::Key(key) => key.is_activation(),
+        InputEvent::SwitchPress { .. } | InputEvent::Voice { .. } => true,
+        _ => event.cell_position().is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::KeyEvent;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_disabled_methods_dropped() {
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.dwell_enabled = true;
+        let mut router = InputRouter::new(RouterConfig::from_settings(&settings));
+
+        assert_eq!(router.route(InputEvent::switch_press(), ms(0)), None);
+        assert_eq!(
+            router.route(InputEvent::SwitchRelease { switch_id: 0 }, ms(50)),
+            None
+        );
+        let dwell = InputEvent::dwell(0, 0, ms(1000));
+        assert_eq!(router.route(dwell.clone(), ms(100)), Some(dwell));
+
+        // Morse code needs switches too
+        settings.input.morse.enabled = true;
+        assert!(RouterConfig::from_settings(&settings).switch);
+    }
+
+    #[test]
+    fn test_held_switch_blocks_other_selections() {
+        let mut router = InputRouter::default();
+        router.route(InputEvent::switch_press(), ms(0));
+        assert_eq!(router.route(InputEvent::tap(0, 0), ms(2000)), None);
+
+        // Navigation still passes, and selection resumes after release
+        let key = InputEvent::Key(KeyEvent::Down);
+        assert_eq!(router.route(key.clone(), ms(2000)), Some(key));
+        let release = InputEvent::SwitchRelease { switch_id: 0 };
+        assert_eq!(router.route(release.clone(), ms(2100)), Some(release));
+        assert!(router.route(InputEvent::tap(0, 0), ms(2200)).is_some());
+    }
+
+    #[test]
+    fn test_unreleased_switch_expires() {
+        let mut router = InputRouter::default();
+        router.route(InputEvent::switch_press(), ms(0));
+        assert_eq!(router.route(InputEvent::tap(0, 0), ms(2900)), None);
+        assert!(router.route(InputEvent::tap(0, 0), ms(3000)).is_some());
+
+        // A late release is still passed on with its press
+        let release = InputEvent::SwitchRelease { switch_id: 0 };
+        assert_eq!(router.route(release.clone(), ms(3100)), Some(release));
+    }
+
+    #[test]
+    fn test_conflict_window() {
+        let mut router = InputRouter::default();
+        assert!(router.route(InputEvent::tap(0, 0), ms(0)).is_some());
+
+        // Another method right after the tap is dropped, with its release
+        assert_eq!(router.route(InputEvent::switch_press(), ms(200)), None);
+        assert_eq!(
+            router.route(InputEvent::SwitchRelease { switch_id: 0 }, ms(250)),
+            None
+        );
+        assert_eq!(
+            router.route(InputEvent::dwell(0, 1, ms(1000)), ms(300)),
+            None
+        );
+
+        // The same method is left to the key guard
+        assert!(router.route(InputEvent::tap(0, 1), ms(300)).is_some());
+        assert!(router.route(InputEvent::switch_press(), ms(800)).is_some());
+    }
+
+    #[test]
+    fn test_direct_selection_pauses_scanning() {
+        let mut router = InputRouter::default();
+        router.route(InputEvent::switch_press(), ms(0));
+        router.route(InputEvent::SwitchRelease { switch_id: 0 }, ms(100));
+        assert!(!router.is_scan_paused(ms(100)));
+
+        router.route(InputEvent::tap(1, 1), ms(1000));
+        assert!(router.is_scan_paused(ms(2500)));
+        assert!(!router.is_scan_paused(ms(3000)));
+        assert_eq!(router.scan_paused_until(), Some(ms(3000)));
+    }
+}
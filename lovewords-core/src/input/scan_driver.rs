@@ -0,0 +1,596 @@
+//! Timed auto-scanning.
+//!
+//! [`ScanDriver`] owns a [`Scanner`] and advances it on a schedule, so hosts
+//! only need to call [`ScanDriver::tick`] from their frame loop or timer and
+//! forward switch presses. The driver applies the [`ScanConfig`] timing rules:
+//!
+//! - the highlight moves every `scan_interval`
+//! - the first item after starting, wrapping around or entering a row,
+//!   column or block stays highlighted for an extra `first_item_delay`
+//! - scanning stops after `max_cycles` unanswered cycles
+//! - nothing moves on its own when `auto_scan` is off
+//!
+//! Switch presses and releases go through [`ScanDriver::on_switch_down`] and
+//! [`ScanDriver::on_switch_up`], which map each switch to a [`SwitchAction`]
+//! (from [`InputSettings`]) and apply the configured [`ScanTechnique`]: auto,
+//! step, inverse or critical-overscan scanning.
+//!
+//! Times are [`Duration`]s from a [`Clock`], so tests can drive the scanner
+//! with a [`ManualClock`](super::ManualClock) and never sleep.
+//!
+//! [`ScanConfig`]: super::scanning::ScanConfig
+//! [`ScanTechnique`]: super::scanning::ScanTechnique
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::storage::{InputSettings, SwitchAction};
+
+use super::clock::Clock;
+use super::scanning::{ScanState, ScanTechnique, Scanner};
+
+/// What a switch event did to the scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchOutcome {
+    /// Nothing changed.
+    Ignored,
+
+    /// The highlight or scan level changed.
+    Moved(ScanState),
+
+    /// A cell was selected.
+    Selected { row: usize, col: usize },
+
+    /// The user asked to hear the highlighted item.
+    Speak(ScanState),
+}
+
+/// Drives a [`Scanner`] from clock ticks.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use lovewords_core::input::{ManualClock, ScanDriver, ScanState, Scanner};
+///
+/// let clock = Arc::new(ManualClock::new());
+/// let mut driver = ScanDriver::new(Scanner::new(3, 3), clock.clone());
+/// driver.start();
+///
+/// // The first row gets the interval plus the first-item delay
+/// clock.advance(Duration::from_millis(1000));
+/// assert_eq!(driver.update(), None);
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(driver.update(), Some(ScanState::ScanningRows { current_row: 1 }));
+/// ```
+pub struct ScanDriver {
+    scanner: Scanner,
+    clock: Arc<dyn Clock>,
+
+    /// When the highlight should next move, if it moves on its own.
+    next_due: Option<Duration>,
+
+    /// Action for each switch, by switch id.
+    switch_actions: BTreeMap<u8, SwitchAction>,
+
+    /// Whether the inverse-scanning switch is held down.
+    held: bool,
+
+    /// Whether critical overscan is in its slow phase.
+    slow: bool,
+}
+
+impl ScanDriver {
+    /// Create a driver for a scanner.
+    pub fn new(scanner: Scanner, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            scanner,
+            clock,
+            next_due: None,
+            switch_actions: BTreeMap::from([(0, SwitchAction::Select), (1, SwitchAction::Back)]),
+            held: false,
+            slow: false,
+        }
+    }
+
+    /// Set the actions for the primary and secondary switches.
+    pub fn with_switch_actions(mut self, primary: SwitchAction, secondary: SwitchAction) -> Self {
+        self.switch_actions.insert(0, primary);
+        self.switch_actions.insert(1, secondary);
+        self
+    }
+
+    /// Set the action for any switch, including ones beyond the second.
+    pub fn with_switch_action(mut self, switch_id: u8, action: SwitchAction) -> Self {
+        self.switch_actions.insert(switch_id, action);
+        self
+    }
+
+    /// Apply input settings; see [`apply_input_settings`](Self::apply_input_settings).
+    pub fn with_input_settings(mut self, settings: &InputSettings) -> Self {
+        self.apply_input_settings(settings);
+        self
+    }
+
+    /// Apply switch actions and the scan technique from input settings.
+    pub fn apply_input_settings(&mut self, settings: &InputSettings) {
+        self.switch_actions = settings.switch_actions();
+        let mut config = self.scanner.config().clone();
+        config.technique = settings.scan_technique;
+        self.scanner = self.scanner.clone().with_config(config);
+    }
+
+    /// Replace the clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Get the current time from the clock.
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Get the action for a switch, if it has one.
+    ///
+    /// Switch 0 is primary and switch 1 is secondary.
+    pub fn switch_action(&self, switch_id: u8) -> Option<SwitchAction> {
+        self.switch_actions.get(&switch_id).copied()
+    }
+
+    /// Get the scanner.
+    pub fn scanner(&self) -> &Scanner {
+        &self.scanner
+    }
+
+    /// Get mutable access to the scanner.
+    ///
+    /// Call [`reschedule`](Self::reschedule) after changing its state.
+    pub fn scanner_mut(&mut self) -> &mut Scanner {
+        &mut self.scanner
+    }
+
+    /// Get the current scan state.
+    pub fn state(&self) -> ScanState {
+        self.scanner.state()
+    }
+
+    /// Get when the highlight will next move, if scheduled.
+    pub fn next_due(&self) -> Option<Duration> {
+        self.next_due
+    }
+
+    /// Get how long until the highlight next moves, if scheduled.
+    pub fn time_until_next(&self, now: Duration) -> Option<Duration> {
+        self.next_due.map(|due| due.saturating_sub(now))
+    }
+
+    /// Start scanning now.
+    pub fn start(&mut self) -> ScanState {
+        self.start_at(self.clock.now())
+    }
+
+    /// Start scanning at the given time.
+    pub fn start_at(&mut self, now: Duration) -> ScanState {
+        self.scanner.start();
+        self.slow = false;
+        self.schedule(now, true);
+        self.scanner.state()
+    }
+
+    /// Stop scanning.
+    pub fn stop(&mut self) {
+        self.scanner.stop();
+        self.next_due = None;
+        self.held = false;
+        self.slow = false;
+    }
+
+    /// Advance the scanner if it is due, using the clock's time.
+    ///
+    /// Returns the new state if the highlight changed.
+    pub fn update(&mut self) -> Option<ScanState> {
+        self.tick(self.clock.now())
+    }
+
+    /// Advance the scanner if it is due at `now`.
+    ///
+    /// Moves at most one step per call: a late tick moves the highlight once
+    /// and restarts the interval rather than skipping items the user never
+    /// saw. Returns the new state if the highlight changed.
+    pub fn tick(&mut self, now: Duration) -> Option<ScanState> {
+        let due = self.next_due?;
+        if now < due {
+            return None;
+        }
+
+        let before = self.scanner.state();
+        let cycles = self.scanner.cycle_count();
+        if !self.scanner.advance() {
+            self.next_due = None;
+            return Some(self.scanner.state());
+        }
+
+        let wrapped = self.scanner.cycle_count() != cycles;
+        if wrapped && self.scanner.cycle_count() == 0 {
+            // Back at the top level after an unanswered nested scan
+            self.slow = false;
+        }
+        self.schedule(now, wrapped);
+        let after = self.scanner.state();
+        (after != before).then_some(after)
+    }
+
+    /// Handle a primary switch press at the given time.
+    ///
+    /// Always selects, whatever the technique. Returns the selected position
+    /// if a cell was selected.
+    pub fn on_switch_press(&mut self, now: Duration) -> Option<(usize, usize)> {
+        let selected = self.scanner.on_switch_press();
+        self.slow = false;
+        self.schedule(now, true);
+        selected
+    }
+
+    /// Handle a secondary switch press at the given time.
+    pub fn on_secondary_switch(&mut self, now: Duration) {
+        self.scanner.on_secondary_switch();
+        self.slow = false;
+        self.schedule(now, true);
+    }
+
+    /// Handle a switch going down.
+    ///
+    /// The switch's [`SwitchAction`] is applied according to the scan
+    /// technique. With inverse scanning the select switch starts moving the
+    /// highlight instead, and selection happens on release.
+    pub fn on_switch_down(&mut self, switch_id: u8, now: Duration) -> SwitchOutcome {
+        let Some(action) = self.switch_action(switch_id) else {
+            return SwitchOutcome::Ignored;
+        };
+        let before = self.scanner.state();
+
+        match action {
+            SwitchAction::Select if self.technique() == ScanTechnique::Inverse => {
+                if !self.scanner.is_scanning() {
+                    self.scanner.start();
+                }
+                self.held = true;
+                self.schedule(now, false);
+            }
+
+            SwitchAction::Select
+                if self.technique() == ScanTechnique::CriticalOverscan
+                    && !self.slow
+                    && self.scanner.is_scanning() =>
+            {
+                // Back up over the item the user probably meant, then slow down
+                self.scanner.retreat();
+                self.slow = true;
+                self.schedule(now, true);
+            }
+
+            SwitchAction::Select => {
+                if let Some((row, col)) = self.on_switch_press(now) {
+                    return SwitchOutcome::Selected { row, col };
+                }
+            }
+
+            SwitchAction::Back => self.on_secondary_switch(now),
+
+            SwitchAction::Next => {
+                if self.scanner.is_scanning() {
+                    self.scanner.advance();
+                    self.schedule(now, false);
+                } else {
+                    self.start_at(now);
+                }
+            }
+
+            SwitchAction::Previous => {
+                if self.scanner.is_scanning() {
+                    self.scanner.retreat();
+                    self.schedule(now, false);
+                } else {
+                    self.start_at(now);
+                }
+            }
+
+            SwitchAction::Speak => return SwitchOutcome::Speak(before),
+        }
+
+        self.outcome(before)
+    }
+
+    /// Handle a switch being released.
+    ///
+    /// Only inverse scanning reacts to releases: releasing the select switch
+    /// selects the highlighted item.
+    pub fn on_switch_up(&mut self, switch_id: u8, now: Duration) -> SwitchOutcome {
+        let is_select = self.switch_action(switch_id) == Some(SwitchAction::Select);
+        if !is_select || !self.held || self.technique() != ScanTechnique::Inverse {
+            return SwitchOutcome::Ignored;
+        }
+
+        self.held = false;
+        let before = self.scanner.state();
+        let selected = self.scanner.on_switch_press();
+        self.schedule(now, true);
+        match selected {
+            Some((row, col)) => SwitchOutcome::Selected { row, col },
+            None => self.outcome(before),
+        }
+    }
+
+    fn technique(&self) -> ScanTechnique {
+        self.scanner.config().technique
+    }
+
+    fn outcome(&self, before: ScanState) -> SwitchOutcome {
+        let after = self.scanner.state();
+        if after != before {
+            SwitchOutcome::Moved(after)
+        } else {
+            SwitchOutcome::Ignored
+        }
+    }
+
+    /// Restart the interval from `now`, e.g. after changing the scanner.
+    pub fn reschedule(&mut self, now: Duration) {
+        self.schedule(now, true);
+    }
+
+    /// Schedule the next step, adding the first-item delay if requested.
+    fn schedule(&mut self, now: Duration, first_item: bool) {
+        let config = self.scanner.config();
+        let timed = match config.technique {
+            ScanTechnique::Inverse => self.held,
+            technique => config.auto_scan && technique.is_timed(),
+        };
+        self.next_due = if timed && self.scanner.is_scanning() {
+            let interval = if self.slow {
+                config.overscan_interval
+            } else {
+                config.scan_interval
+            };
+            let delay = if first_item {
+                config.first_item_delay
+            } else {
+                Duration::ZERO
+            };
+            Some(now + interval + delay)
+        } else {
+            None
+        };
+    }
+}
+
+impl std::fmt::Debug for ScanDriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScanDriver")
+            .field("scanner", &self.scanner)
+            .field("next_due", &self.next_due)
+            .field("switch_actions", &self.switch_actions)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::binding::BindingMap;
+    use crate::input::clock::ManualClock;
+    use crate::input::scanning::{ScanConfig, ScanMode};
+
+    fn with_technique(technique: ScanTechnique) -> Scanner {
+        Scanner::new(3, 3).with_config(ScanConfig {
+            technique,
+            ..Default::default()
+        })
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn driver(scanner: Scanner) -> ScanDriver {
+        ScanDriver::new(scanner, Arc::new(ManualClock::new()))
+    }
+
+    #[test]
+    fn test_interval_and_first_item_delay() {
+        let mut driver = driver(Scanner::new(3, 3));
+        driver.start_at(ms(0));
+        assert_eq!(driver.next_due(), Some(ms(1500)));
+
+        // Nothing moves before the first item's extended time is up
+        assert_eq!(driver.tick(ms(1000)), None);
+        assert_eq!(driver.tick(ms(1499)), None);
+        assert_eq!(
+            driver.tick(ms(1500)),
+            Some(ScanState::ScanningRows { current_row: 1 })
+        );
+
+        // Later items get the plain interval
+        assert_eq!(driver.next_due(), Some(ms(2500)));
+        assert_eq!(
+            driver.tick(ms(2500)),
+            Some(ScanState::ScanningRows { current_row: 2 })
+        );
+
+        // Wrapping back to the first row applies the delay again
+        driver.tick(ms(3500));
+        assert_eq!(driver.state(), ScanState::ScanningRows { current_row: 0 });
+        assert_eq!(driver.next_due(), Some(ms(5000)));
+    }
+
+    #[test]
+    fn test_late_tick_moves_once() {
+        let mut driver = driver(Scanner::new(4, 1));
+        driver.start_at(ms(0));
+
+        // A tick long after the deadline still moves one row
+        assert_eq!(
+            driver.tick(ms(10_000)),
+            Some(ScanState::ScanningRows { current_row: 1 })
+        );
+        assert_eq!(driver.next_due(), Some(ms(11_000)));
+    }
+
+    #[test]
+    fn test_max_cycles_stops() {
+        let config = ScanConfig {
+            max_cycles: 1,
+            ..Default::default()
+        };
+        let mut driver = driver(Scanner::new(2, 2).with_config(config));
+        driver.start_at(ms(0));
+
+        driver.tick(ms(1500));
+        assert_eq!(driver.tick(ms(2500)), Some(ScanState::Idle));
+        assert_eq!(driver.next_due(), None);
+        assert_eq!(driver.tick(ms(10_000)), None);
+    }
+
+    #[test]
+    fn test_switch_press_restarts_timing() {
+        let mut driver = driver(Scanner::new(3, 3).with_mode(ScanMode::RowColumn));
+        driver.start_at(ms(0));
+        driver.tick(ms(1500));
+
+        // Selecting a row gives its first cell the extended time
+        assert_eq!(driver.on_switch_press(ms(1800)), None);
+        assert_eq!(driver.next_due(), Some(ms(3300)));
+
+        // Selecting a cell stops the timer
+        assert_eq!(driver.on_switch_press(ms(2000)), Some((1, 0)));
+        assert_eq!(driver.next_due(), None);
+    }
+
+    #[test]
+    fn test_manual_scanning_never_moves() {
+        let config = ScanConfig {
+            auto_scan: false,
+            ..Default::default()
+        };
+        let mut driver = driver(Scanner::new(3, 3).with_config(config));
+        driver.start_at(ms(0));
+        assert_eq!(driver.next_due(), None);
+        assert_eq!(driver.tick(ms(60_000)), None);
+    }
+
+    #[test]
+    fn test_update_uses_clock() {
+        let clock = Arc::new(ManualClock::new());
+        let mut driver = ScanDriver::new(Scanner::new(2, 2), clock.clone());
+        driver.start();
+
+        clock.advance(ms(1499));
+        assert_eq!(driver.update(), None);
+        clock.advance(ms(1));
+        assert!(driver.update().is_some());
+        assert_eq!(driver.time_until_next(clock.now()), Some(ms(1000)));
+    }
+
+    #[test]
+    fn test_step_scanning() {
+        let mut driver = driver(with_technique(ScanTechnique::Step))
+            .with_switch_actions(SwitchAction::Next, SwitchAction::Select);
+
+        // The first press starts scanning; nothing moves on its own
+        driver.on_switch_down(0, ms(0));
+        assert_eq!(driver.state(), ScanState::ScanningRows { current_row: 0 });
+        assert_eq!(driver.next_due(), None);
+
+        assert_eq!(
+            driver.on_switch_down(0, ms(100)),
+            SwitchOutcome::Moved(ScanState::ScanningRows { current_row: 1 })
+        );
+        driver.on_switch_down(1, ms(200));
+        driver.on_switch_down(0, ms(300));
+        assert_eq!(
+            driver.on_switch_down(1, ms(400)),
+            SwitchOutcome::Selected { row: 1, col: 1 }
+        );
+        assert_eq!(driver.tick(ms(60_000)), None);
+    }
+
+    #[test]
+    fn test_inverse_scanning() {
+        let mut driver = driver(with_technique(ScanTechnique::Inverse));
+
+        // Holding the switch moves the highlight
+        driver.on_switch_down(0, ms(0));
+        assert_eq!(driver.next_due(), Some(ms(1000)));
+        driver.tick(ms(1000));
+        driver.tick(ms(2000));
+        assert_eq!(driver.state(), ScanState::ScanningRows { current_row: 2 });
+
+        // Releasing selects the row and stops moving
+        assert_eq!(
+            driver.on_switch_up(0, ms(2100)),
+            SwitchOutcome::Moved(ScanState::ScanningColumns {
+                row: 2,
+                current_col: 0
+            })
+        );
+        assert_eq!(driver.tick(ms(60_000)), None);
+
+        // Hold again to move through the row, release to select
+        driver.on_switch_down(0, ms(3000));
+        driver.tick(ms(4000));
+        assert_eq!(
+            driver.on_switch_up(0, ms(4200)),
+            SwitchOutcome::Selected { row: 2, col: 1 }
+        );
+
+        // Releases of other switches are ignored
+        assert_eq!(driver.on_switch_up(1, ms(5000)), SwitchOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_critical_overscan() {
+        let mut driver = driver(with_technique(ScanTechnique::CriticalOverscan));
+        driver.start_at(ms(0));
+        driver.tick(ms(1500));
+        driver.tick(ms(2500));
+        assert_eq!(driver.state(), ScanState::ScanningRows { current_row: 2 });
+
+        // The first press backs up one row and slows down
+        assert_eq!(
+            driver.on_switch_down(0, ms(2600)),
+            SwitchOutcome::Moved(ScanState::ScanningRows { current_row: 1 })
+        );
+        assert_eq!(driver.next_due(), Some(ms(5100)));
+
+        // The second press selects
+        driver.on_switch_down(0, ms(3000));
+        assert_eq!(
+            driver.state(),
+            ScanState::ScanningColumns {
+                row: 1,
+                current_col: 0
+            }
+        );
+        assert_eq!(driver.next_due(), Some(ms(4500)));
+    }
+
+    #[test]
+    fn test_switch_actions_from_settings() {
+        let settings = InputSettings {
+            primary_switch_action: SwitchAction::Next,
+            secondary_switch_action: SwitchAction::Select,
+            scan_technique: ScanTechnique::Step,
+            bindings: BindingMap::new().with_switch_action(2, SwitchAction::Speak),
+            ..Default::default()
+        };
+        let driver = driver(Scanner::new(2, 2)).with_input_settings(&settings);
+
+        assert_eq!(driver.switch_action(0), Some(SwitchAction::Next));
+        assert_eq!(driver.switch_action(1), Some(SwitchAction::Select));
+        assert_eq!(driver.switch_action(2), Some(SwitchAction::Speak));
+        assert_eq!(driver.switch_action(3), None);
+        assert_eq!(driver.scanner().config().technique, ScanTechnique::Step);
+    }
+}
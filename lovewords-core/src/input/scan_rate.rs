@@ -0,0 +1,310 @@
+//! Adaptive scan rate.
+//!
+//! A scan interval set by hand is a guess, and the right value changes
+//! through the day as the user tires. [`ScanRateTuner`] measures how long
+//! the user takes to press the switch after a highlight appears, and counts
+//! overshoots: selections cancelled straight away because the highlight had
+//! already moved past the intended item. From these it suggests a new
+//! interval within the caregiver's bounds in [`ScanRateSettings`].
+//!
+//! The tuner only learns; a [`Session`](crate::session::Session) decides
+//! whether to apply or suggest changes according to [`ScanRateMode`], and
+//! the learned interval can be saved back to
+//! [`AccessibilitySettings::scan_interval`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use lovewords_core::input::ScanRateTuner;
+//! use lovewords_core::storage::ScanRateSettings;
+//!
+//! let ms = Duration::from_millis;
+//! let mut tuner = ScanRateTuner::new(ScanRateSettings::default(), ms(1000));
+//!
+//! // The user reliably reacts within 400ms, so scanning can go faster
+//! for i in 0..10 {
+//!     tuner.on_highlight(ms(i * 1000));
+//!     tuner.on_select(ms(i * 1000 + 400));
+//! }
+//! assert_eq!(tuner.suggestion(), Some(ms(750)));
+//! ```
+//!
+//! [`ScanRateMode`]: crate::storage::ScanRateMode
+//! [`AccessibilitySettings::scan_interval`]: crate::storage::AccessibilitySettings::scan_interval
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::storage::ScanRateSettings;
+
+/// How soon after a selection a cancel counts as an overshoot.
+const CANCEL_WINDOW: Duration = Duration::from_millis(2000);
+
+/// Share of reactions that should fit comfortably in the interval.
+const REACTION_PERCENTILE: f32 = 0.9;
+
+/// Headroom added to the slow reactions, as a fraction.
+const MARGIN: f32 = 0.25;
+
+/// Overshoot rate above which scanning is slowed down regardless.
+const OVERSHOOT_LIMIT: f32 = 0.2;
+
+/// Largest change made at once, as a fraction of the interval.
+const MAX_STEP: f32 = 0.25;
+
+/// One measured selection.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    reaction: Duration,
+    cancelled: bool,
+}
+
+/// Learns a scan interval from switch reaction times.
+#[derive(Debug, Clone)]
+pub struct ScanRateTuner {
+    settings: ScanRateSettings,
+    interval: Duration,
+    onset: Option<Duration>,
+    last_selection: Option<Duration>,
+    samples: VecDeque<Sample>,
+    suggested: Option<Duration>,
+}
+
+impl ScanRateTuner {
+    /// Create a tuner starting from the current interval.
+    pub fn new(settings: ScanRateSettings, interval: Duration) -> Self {
+        Self {
+            settings,
+            interval,
+            onset: None,
+            last_selection: None,
+            samples: VecDeque::new(),
+            suggested: None,
+        }
+    }
+
+    /// Get the settings.
+    pub fn settings(&self) -> &ScanRateSettings {
+        &self.settings
+    }
+
+    /// Get the interval the tuner is measuring against.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Set the interval, e.g. after a caregiver changed it, and start
+    /// measuring afresh.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+        self.samples.clear();
+        self.suggested = None;
+    }
+
+    /// Get the number of selections measured at the current interval.
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Record that the highlight moved to a new item at `now`.
+    pub fn on_highlight(&mut self, now: Duration) {
+        self.onset = Some(now);
+    }
+
+    /// Record a selection press at `now`.
+    ///
+    /// Presses without a highlight since the last one are ignored.
+    pub fn on_select(&mut self, now: Duration) {
+        let Some(onset) = self.onset.take() else {
+            return;
+        };
+        self.samples.push_back(Sample {
+            reaction: now.saturating_sub(onset),
+            cancelled: false,
+        });
+        while self.samples.len() > self.settings.samples.max(1) {
+            self.samples.pop_front();
+        }
+        self.last_selection = Some(now);
+    }
+
+    /// Record a cancel (back, backspace) at `now`.
+    ///
+    /// A cancel right after a selection marks it as an overshoot.
+    pub fn on_cancel(&mut self, now: Duration) {
+        let Some(selected) = self.last_selection.take() else {
+            return;
+        };
+        if now.saturating_sub(selected) <= CANCEL_WINDOW {
+            if let Some(sample) = self.samples.back_mut() {
+                sample.cancelled = true;
+            }
+        }
+    }
+
+    /// Get the share of selections that were overshoots.
+    pub fn overshoot_rate(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let cancelled = self.samples.iter().filter(|s| s.cancelled).count();
+        cancelled as f32 / self.samples.len() as f32
+    }
+
+    /// Get the interval the measurements call for, if there are enough of
+    /// them and it differs noticeably from the current one.
+    ///
+    /// Changes are limited to a quarter of the interval at a time and kept
+    /// within the configured bounds.
+    pub fn suggestion(&self) -> Option<Duration> {
+        if self.samples.len() < self.settings.samples.max(1) {
+            return None;
+        }
+
+        // Overshoot reaction times measure the wrong item, so leave them out
+        let mut reactions: Vec<Duration> = self
+            .samples
+            .iter()
+            .filter(|s| !s.cancelled)
+            .map(|s| s.reaction)
+            .collect();
+        reactions.sort();
+        let mut target = match reactions.len() {
+            0 => self.interval,
+            n => {
+                let index = ((n as f32 * REACTION_PERCENTILE).ceil() as usize).clamp(1, n) - 1;
+                reactions[index].mul_f32(1.0 + MARGIN)
+            }
+        };
+        let overshoots = self.overshoot_rate();
+        if overshoots > OVERSHOOT_LIMIT {
+            target = target.max(self.interval.mul_f32(1.0 + overshoots));
+        }
+
+        let target = target
+            .clamp(
+                self.interval.mul_f32(1.0 - MAX_STEP),
+                self.interval.mul_f32(1.0 + MAX_STEP),
+            )
+            .clamp(self.settings.min_interval, self.settings.max_interval);
+        let target = Duration::from_millis((target.as_millis() as u64).div_ceil(10) * 10);
+
+        let change = target.abs_diff(self.interval);
+        (change >= self.interval / 20).then_some(target)
+    }
+
+    /// Get the suggestion if it changed since the last call, so it is only
+    /// offered to a caregiver once.
+    pub fn new_suggestion(&mut self) -> Option<Duration> {
+        let suggestion = self.suggestion();
+        if suggestion == self.suggested {
+            return None;
+        }
+        self.suggested = suggestion;
+        suggestion
+    }
+
+    /// Adopt the suggested interval, if any, and start measuring afresh.
+    ///
+    /// Returns the new interval.
+    pub fn apply(&mut self) -> Option<Duration> {
+        let interval = self.suggestion()?;
+        self.set_interval(interval);
+        Some(interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn tuner(interval: u64) -> ScanRateTuner {
+        ScanRateTuner::new(
+            ScanRateSettings {
+                samples: 5,
+                ..ScanRateSettings::default()
+            },
+            ms(interval),
+        )
+    }
+
+    fn react(tuner: &mut ScanRateTuner, times: &[u64]) {
+        for (i, reaction) in times.iter().enumerate() {
+            let onset = i as u64 * 10_000;
+            tuner.on_highlight(ms(onset));
+            tuner.on_select(ms(onset + reaction));
+        }
+    }
+
+    #[test]
+    fn test_needs_enough_samples() {
+        let mut tuner = tuner(1000);
+        react(&mut tuner, &[300, 300, 300, 300]);
+        assert_eq!(tuner.suggestion(), None);
+
+        // A press without a new highlight isn't a reaction
+        tuner.on_select(ms(99_000));
+        assert_eq!(tuner.sample_count(), 4);
+    }
+
+    #[test]
+    fn test_slow_reactions_lengthen_interval() {
+        let mut tuner = tuner(1000);
+        react(&mut tuner, &[700, 850, 900, 950, 800]);
+        assert_eq!(tuner.suggestion(), Some(ms(1190)));
+        assert_eq!(tuner.new_suggestion(), Some(ms(1190)));
+        assert_eq!(tuner.new_suggestion(), None);
+
+        assert_eq!(tuner.apply(), Some(ms(1190)));
+        assert_eq!(tuner.interval(), ms(1190));
+        assert_eq!(tuner.sample_count(), 0);
+    }
+
+    #[test]
+    fn test_steady_reactions_keep_interval() {
+        let mut tuner = tuner(1000);
+        react(&mut tuner, &[750, 780, 800, 760, 790]);
+        assert_eq!(tuner.suggestion(), None);
+    }
+
+    #[test]
+    fn test_overshoots_slow_down() {
+        let mut tuner = tuner(1000);
+        for i in 0..5 {
+            let onset = i * 10_000;
+            tuner.on_highlight(ms(onset));
+            tuner.on_select(ms(onset + 500));
+            // Two selections are cancelled at once
+            if i % 2 == 1 {
+                tuner.on_cancel(ms(onset + 1500));
+            }
+        }
+        assert_eq!(tuner.overshoot_rate(), 0.4);
+        assert_eq!(tuner.suggestion(), Some(ms(1250)));
+
+        // A late cancel isn't an overshoot; the oldest sample drops out
+        tuner.on_highlight(ms(60_000));
+        tuner.on_select(ms(60_500));
+        tuner.on_cancel(ms(70_000));
+        assert_eq!(tuner.overshoot_rate(), 0.4);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut tuner = ScanRateTuner::new(
+            ScanRateSettings {
+                samples: 3,
+                min_interval: ms(900),
+                ..ScanRateSettings::default()
+            },
+            ms(1000),
+        );
+        react(&mut tuner, &[100, 100, 100]);
+        assert_eq!(tuner.suggestion(), Some(ms(900)));
+    }
+}
@@ -7,7 +7,9 @@
 //!
 //! - **Row-Column**: First scan highlights rows, then columns within selected row
 //! - **Linear**: Scan through cells one at a time
-//! - **Block**: Scan through groups of cells
+//! - **Block**: Scan through groups of cells, then rows and cells within the
+//!   selected group. Groups come from the board's `ext_lovewords_scan_blocks`
+//!   extension, or default to quadrants of the grid.
 //!
 //! # Timing
 //!
@@ -17,6 +19,9 @@
 
 use std::time::Duration;
 
+use crate::board::Board;
+use crate::obf::{ObfBoard, ObfScanBlock};
+
 /// Scanning mode for switch access.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum ScanMode {
@@ -30,12 +35,87 @@ pub enum ScanMode {
     /// Column-row scanning: first select a column, then a cell within that column.
     ColumnRow,
 
-    /// Block scanning: scan through groups of cells (requires block definitions).
+    /// Block scanning: scan through groups of cells, then rows and cells
+    /// within the selected group.
     Block,
 }
 
-/// Current state of the scanner.
+/// A rectangular group of cells for block scanning.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanBlock {
+    /// Top row of the block.
+    pub row: usize,
+    /// Left column of the block.
+    pub col: usize,
+    /// Number of rows in the block.
+    pub rows: usize,
+    /// Number of columns in the block.
+    pub cols: usize,
+}
+
+impl ScanBlock {
+    /// Create a block from its top-left corner and size.
+    pub fn new(row: usize, col: usize, rows: usize, cols: usize) -> Self {
+        Self {
+            row,
+            col,
+            rows,
+            cols,
+        }
+    }
+
+    /// Split a grid into up to four quadrants, in reading order.
+    ///
+    /// Odd sizes give the extra row or column to the top/left quadrants.
+    /// Grids with a single row or column split into halves instead.
+    pub fn quadrants(rows: usize, cols: usize) -> Vec<ScanBlock> {
+        let top = rows.div_ceil(2);
+        let left = cols.div_ceil(2);
+        let mut blocks = Vec::with_capacity(4);
+        for (row, height) in [(0, top), (top, rows - top)] {
+            for (col, width) in [(0, left), (left, cols - left)] {
+                if height > 0 && width > 0 {
+                    blocks.push(ScanBlock::new(row, col, height, width));
+                }
+            }
+        }
+        blocks
+    }
+
+    /// Index of the last row in the block; the first row if it has none.
+    pub fn last_row(&self) -> usize {
+        self.row + self.rows.saturating_sub(1)
+    }
+
+    /// Index of the last column in the block; the first column if it has
+    /// none.
+    pub fn last_col(&self) -> usize {
+        self.col + self.cols.saturating_sub(1)
+    }
+
+    /// Check if the block contains a position.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.row..self.row + self.rows).contains(&row)
+            && (self.col..self.col + self.cols).contains(&col)
+    }
+
+    /// Clip the block to a grid, returning `None` if nothing is left.
+    fn clamp_to(self, rows: usize, cols: usize) -> Option<ScanBlock> {
+        let height = rows.saturating_sub(self.row).min(self.rows);
+        let width = cols.saturating_sub(self.col).min(self.cols);
+        (height > 0 && width > 0).then(|| ScanBlock::new(self.row, self.col, height, width))
+    }
+}
+
+impl From<ObfScanBlock> for ScanBlock {
+    fn from(block: ObfScanBlock) -> Self {
+        Self::new(block.row, block.col, block.rows, block.cols)
+    }
+}
+
+/// Current state of the scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ScanState {
     /// Not currently scanning.
     Idle,
@@ -55,6 +135,19 @@ pub enum ScanState {
     /// Scanning through rows in a selected column.
     ScanningRowsInCol { col: usize, current_row: usize },
 
+    /// Scanning through blocks (block mode).
+    ScanningBlocks { current_block: usize },
+
+    /// Scanning through rows in a selected block.
+    ScanningBlockRows { block: usize, current_row: usize },
+
+    /// Scanning through cells in a row of a selected block.
+    ScanningBlockCells {
+        block: usize,
+        row: usize,
+        current_col: usize,
+    },
+
     /// Cell is selected, awaiting confirmation or next action.
     Selected { row: usize, col: usize },
 }
@@ -66,6 +159,9 @@ impl ScanState {
             ScanState::ScanningColumns { row, current_col } => Some((*row, *current_col)),
             ScanState::ScanningLinear { row, col } => Some((*row, *col)),
             ScanState::ScanningRowsInCol { col, current_row } => Some((*current_row, *col)),
+            ScanState::ScanningBlockCells {
+                row, current_col, ..
+            } => Some((*row, *current_col)),
             ScanState::Selected { row, col } => Some((*row, *col)),
             _ => None,
         }
@@ -77,17 +173,97 @@ impl ScanState {
             ScanState::ScanningRows { current_row } => Some(*current_row),
             ScanState::ScanningColumns { row, .. } => Some(*row),
             ScanState::ScanningLinear { row, .. } => Some(*row),
+            ScanState::ScanningBlockRows { current_row, .. } => Some(*current_row),
+            ScanState::ScanningBlockCells { row, .. } => Some(*row),
             ScanState::Selected { row, .. } => Some(*row),
             _ => None,
         }
     }
 
+    /// Get the index of the currently highlighted block, if any.
+    pub fn highlighted_block(&self) -> Option<usize> {
+        match self {
+            ScanState::ScanningBlocks { current_block } => Some(*current_block),
+            ScanState::ScanningBlockRows { block, .. } => Some(*block),
+            ScanState::ScanningBlockCells { block, .. } => Some(*block),
+            _ => None,
+        }
+    }
+
     /// Check if scanning is active.
     pub fn is_scanning(&self) -> bool {
         !matches!(self, ScanState::Idle | ScanState::Selected { .. })
     }
 }
 
+/// How the user moves the highlight and makes selections.
+///
+/// Techniques apply on top of any [`ScanMode`]; timing is handled by
+/// [`ScanDriver`](super::ScanDriver).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ScanTechnique {
+    /// The highlight moves on a timer; a switch press selects.
+    #[default]
+    AutoScan,
+
+    /// Each press of one switch moves the highlight; another switch selects.
+    Step,
+
+    /// The highlight moves only while the switch is held; releasing selects.
+    Inverse,
+
+    /// The highlight moves quickly; the first press backs up one item and
+    /// continues slowly, and a second press selects.
+    CriticalOverscan,
+}
+
+impl ScanTechnique {
+    /// Check if the highlight moves on a timer with this technique.
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, ScanTechnique::Step)
+    }
+}
+
+/// Which level of the scan is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanLevel {
+    /// A block of cells.
+    Block,
+    /// A row, or part of a row inside a block.
+    Row,
+    /// A column.
+    Column,
+    /// A single cell.
+    Cell,
+}
+
+/// What the scanner is highlighting, for auditory prompts.
+///
+/// Every highlight covers a rectangle of the grid: a whole row, a whole
+/// column, a block, or a single cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanAnnouncement {
+    /// Level being scanned.
+    pub level: ScanLevel,
+    /// Index of the row, column or block; `0` for cells.
+    pub index: usize,
+    /// Highlighted area of the grid.
+    pub area: ScanBlock,
+}
+
+/// Order in which the scanner visits rows, columns, blocks and cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ScanOrder {
+    /// Grid order: top to bottom, left to right.
+    #[default]
+    Grid,
+
+    /// Most likely first, using the scanner's cell weights (usage counts
+    /// plus `ext_lovewords_priority`). Ties keep grid order; rows, columns
+    /// and blocks are ranked by the total weight of their cells.
+    Frequency,
+}
+
 /// Configuration for the scanner.
 #[derive(Debug, Clone)]
 pub struct ScanConfig {
@@ -103,8 +279,18 @@ pub struct ScanConfig {
     /// Number of complete scan cycles before stopping.
     pub max_cycles: u8,
 
-    /// Whether to include empty cells in linear scan.
+    /// Whether to skip empty and hidden cells, and rows or columns with
+    /// nothing to select.
     pub skip_empty: bool,
+
+    /// How the highlight is moved and selections are made.
+    pub technique: ScanTechnique,
+
+    /// Slower interval used after the first press in critical overscan.
+    pub overscan_interval: Duration,
+
+    /// Order in which items are visited.
+    pub order: ScanOrder,
 }
 
 impl Default for ScanConfig {
@@ -115,11 +301,18 @@ impl Default for ScanConfig {
             auto_scan: true,
             max_cycles: 3,
             skip_empty: true,
+            technique: ScanTechnique::default(),
+            overscan_interval: Duration::from_millis(2000),
+            order: ScanOrder::default(),
         }
     }
 }
 
 /// Switch scanner for accessibility input.
+///
+/// Scanners built from a board (see [`Scanner::from_board`]) skip empty and
+/// hidden positions, along with rows and columns that have nothing to select,
+/// as long as [`ScanConfig::skip_empty`] is set.
 #[derive(Debug, Clone)]
 pub struct Scanner {
     /// Scanning mode.
@@ -137,6 +330,15 @@ pub struct Scanner {
 
     /// Current cycle count.
     cycle_count: u8,
+
+    /// Board-defined blocks for block scanning; quadrants are used if empty.
+    blocks: Vec<ScanBlock>,
+
+    /// `[row][col]` mask of selectable positions; `None` means all of them.
+    occupancy: Option<Vec<Vec<bool>>>,
+
+    /// `[row][col]` cell weights for frequency ordering.
+    weights: Option<Vec<Vec<i64>>>,
 }
 
 impl Scanner {
@@ -149,9 +351,30 @@ impl Scanner {
             cols,
             config: ScanConfig::default(),
             cycle_count: 0,
+            blocks: Vec::new(),
+            occupancy: None,
+            weights: None,
         }
     }
 
+    /// Create a scanner sized to a board that skips its empty and hidden cells.
+    ///
+    /// Uses the board's `ext_lovewords_scan_blocks` for block scanning, if set.
+    pub fn from_board(board: &Board) -> Self {
+        let mut scanner = Self::new(board.rows(), board.cols());
+        scanner.set_board(board.obf());
+        scanner
+    }
+
+    /// Create a scanner from a `[row][col]` mask of selectable positions.
+    ///
+    /// The grid is sized to the mask; short rows are treated as empty.
+    pub fn from_occupancy(occupancy: Vec<Vec<bool>>) -> Self {
+        let rows = occupancy.len();
+        let cols = occupancy.iter().map(Vec::len).max().unwrap_or(0);
+        Self::new(rows, cols).with_occupancy(occupancy)
+    }
+
     /// Set the scanning mode.
     pub fn with_mode(mut self, mode: ScanMode) -> Self {
         self.mode = mode;
@@ -164,6 +387,18 @@ impl Scanner {
         self
     }
 
+    /// Set the blocks used for block scanning.
+    pub fn with_blocks<B: Into<ScanBlock>>(mut self, blocks: Vec<B>) -> Self {
+        self.set_blocks(blocks);
+        self
+    }
+
+    /// Set the mask of selectable positions.
+    pub fn with_occupancy(mut self, occupancy: Vec<Vec<bool>>) -> Self {
+        self.set_occupancy(occupancy);
+        self
+    }
+
     /// Get the current state.
     pub fn state(&self) -> ScanState {
         self.state
@@ -179,22 +414,159 @@ impl Scanner {
         &self.config
     }
 
+    /// Get the number of times the current level has wrapped around.
+    pub fn cycle_count(&self) -> u8 {
+        self.cycle_count
+    }
+
     /// Update grid dimensions (e.g., when changing boards).
+    ///
+    /// Board-defined blocks are cleared. The occupancy mask and cell
+    /// weights are cut or padded to the new size, with new positions empty
+    /// and weighing zero; use [`set_board`](Self::set_board) to recompute
+    /// them from a board.
     pub fn set_grid_size(&mut self, rows: usize, cols: usize) {
         self.rows = rows;
         self.cols = cols;
+        self.blocks.clear();
+        if let Some(occupancy) = &mut self.occupancy {
+            resize_grid(occupancy, rows, cols, false);
+        }
+        if let Some(weights) = &mut self.weights {
+            resize_grid(weights, rows, cols, 0);
+        }
+        self.reset();
+    }
+
+    /// Set `[row][col]` cell weights for [`ScanOrder::Frequency`].
+    ///
+    /// Higher weights are visited first. The order is fixed until the
+    /// weights change, so set them once per board rather than after every
+    /// selection to keep it predictable. Missing positions weigh zero.
+    pub fn set_weights(&mut self, weights: Vec<Vec<i64>>) {
+        self.weights = Some(weights);
+        self.reset();
+    }
+
+    /// Set cell weights for [`ScanOrder::Frequency`].
+    pub fn with_weights(mut self, weights: Vec<Vec<i64>>) -> Self {
+        self.set_weights(weights);
+        self
+    }
+
+    /// Resize the scanner to a board, skipping its empty and hidden cells.
+    ///
+    /// Uses the board's `ext_lovewords_scan_blocks` for block scanning, if set.
+    pub fn set_board(&mut self, board: &ObfBoard) {
+        self.set_grid_size(board.grid.rows, board.grid.columns);
+        self.weights = None;
+        self.set_occupancy(board.occupancy());
+        if let Some(blocks) = &board.extensions.scan_blocks {
+            self.set_blocks(blocks.clone());
+        }
+    }
+
+    /// Set the mask of selectable positions, indexed `[row][col]`.
+    ///
+    /// Positions missing from the mask are treated as empty.
+    pub fn set_occupancy(&mut self, occupancy: Vec<Vec<bool>>) {
+        self.occupancy = Some(occupancy);
+        self.reset();
+    }
+
+    /// Set the blocks used for block scanning.
+    ///
+    /// Blocks are clipped to the grid, and empty ones dropped; an empty
+    /// list falls back to quadrants. Blocks from a board's
+    /// `ext_lovewords_scan_blocks` can be passed as they are.
+    pub fn set_blocks<B: Into<ScanBlock>>(&mut self, blocks: Vec<B>) {
+        self.blocks = blocks
+            .into_iter()
+            .filter_map(|b| b.into().clamp_to(self.rows, self.cols))
+            .collect();
         self.reset();
     }
 
+    /// Get the blocks used for block scanning.
+    pub fn blocks(&self) -> Vec<ScanBlock> {
+        if self.blocks.is_empty() {
+            ScanBlock::quadrants(self.rows, self.cols)
+        } else {
+            self.blocks.clone()
+        }
+    }
+
+    /// Get a block by index.
+    pub fn block(&self, index: usize) -> Option<ScanBlock> {
+        if self.blocks.is_empty() {
+            ScanBlock::quadrants(self.rows, self.cols)
+                .get(index)
+                .copied()
+        } else {
+            self.blocks.get(index).copied()
+        }
+    }
+
+    /// Check if the scanner will stop on a position.
+    pub fn is_scannable(&self, row: usize, col: usize) -> bool {
+        if row >= self.rows || col >= self.cols {
+            return false;
+        }
+        if !self.config.skip_empty {
+            return true;
+        }
+        self.occupancy.as_ref().is_none_or(|mask| {
+            mask.get(row)
+                .and_then(|r| r.get(col))
+                .copied()
+                .unwrap_or(false)
+        })
+    }
+
+    /// Describe the current highlight for auditory scanning.
+    ///
+    /// Returns `None` when idle or once a cell has been selected.
+    pub fn announcement(&self) -> Option<ScanAnnouncement> {
+        let announce = |level, index, area| Some(ScanAnnouncement { level, index, area });
+        match self.state {
+            ScanState::ScanningRows { current_row } => announce(
+                ScanLevel::Row,
+                current_row,
+                ScanBlock::new(current_row, 0, 1, self.cols),
+            ),
+            ScanState::ScanningCols { current_col } => announce(
+                ScanLevel::Column,
+                current_col,
+                ScanBlock::new(0, current_col, self.rows, 1),
+            ),
+            ScanState::ScanningBlocks { current_block } => {
+                announce(ScanLevel::Block, current_block, self.block(current_block)?)
+            }
+            ScanState::ScanningBlockRows { block, current_row } => {
+                let b = self.block(block)?;
+                announce(
+                    ScanLevel::Row,
+                    current_row,
+                    ScanBlock::new(current_row, b.col, 1, b.cols),
+                )
+            }
+            ScanState::ScanningColumns { .. }
+            | ScanState::ScanningLinear { .. }
+            | ScanState::ScanningRowsInCol { .. }
+            | ScanState::ScanningBlockCells { .. } => {
+                let (row, col) = self.state.highlighted_position()?;
+                announce(ScanLevel::Cell, 0, ScanBlock::new(row, col, 1, 1))
+            }
+            ScanState::Idle | ScanState::Selected { .. } => None,
+        }
+    }
+
     /// Start scanning.
+    ///
+    /// Stays idle if there is nothing to scan.
     pub fn start(&mut self) {
         self.cycle_count = 0;
-        self.state = match self.mode {
-            ScanMode::RowColumn => ScanState::ScanningRows { current_row: 0 },
-            ScanMode::Linear => ScanState::ScanningLinear { row: 0, col: 0 },
-            ScanMode::ColumnRow => ScanState::ScanningCols { current_col: 0 },
-            ScanMode::Block => ScanState::ScanningRows { current_row: 0 }, // Fallback
-        };
+        self.state = self.first_state().unwrap_or(ScanState::Idle);
     }
 
     /// Stop scanning and return to idle.
@@ -217,72 +589,86 @@ impl Scanner {
     ///
     /// Returns the selected position if a cell was selected.
     pub fn on_switch_press(&mut self) -> Option<(usize, usize)> {
-        match self.state {
+        let grid = self.grid();
+        let next = match self.state {
             ScanState::Idle => {
                 self.start();
-                None
+                return None;
             }
 
-            ScanState::ScanningRows { current_row } => {
-                // Select this row, start scanning columns
-                self.state = ScanState::ScanningColumns {
-                    row: current_row,
-                    current_col: 0,
-                };
-                None
-            }
+            // Select this row, start scanning columns
+            ScanState::ScanningRows { current_row } => self.enter_row(grid, current_row),
 
-            ScanState::ScanningColumns { row, current_col } => {
-                // Select this cell
-                let pos = (row, current_col);
+            // Select this column, start scanning rows
+            ScanState::ScanningCols { current_col } => self.enter_col(current_col),
+
+            ScanState::ScanningBlocks { current_block } => self.enter_block(current_block),
+
+            // Select this row, start scanning its cells in the block
+            ScanState::ScanningBlockRows { block, current_row } => self
+                .block(block)
+                .and_then(|b| self.enter_block_row(block, b, current_row)),
+
+            // Select this cell
+            ScanState::ScanningColumns { row, current_col }
+            | ScanState::ScanningBlockCells {
+                row, current_col, ..
+            } => {
                 self.state = ScanState::Selected {
                     row,
                     col: current_col,
                 };
-                Some(pos)
+                return Some((row, current_col));
             }
 
             ScanState::ScanningLinear { row, col } => {
-                // Select this cell
-                let pos = (row, col);
                 self.state = ScanState::Selected { row, col };
-                Some(pos)
-            }
-
-            ScanState::ScanningCols { current_col } => {
-                // Select this column, start scanning rows
-                self.state = ScanState::ScanningRowsInCol {
-                    col: current_col,
-                    current_row: 0,
-                };
-                None
+                return Some((row, col));
             }
 
             ScanState::ScanningRowsInCol { col, current_row } => {
-                // Select this cell
-                let pos = (current_row, col);
                 self.state = ScanState::Selected {
                     row: current_row,
                     col,
                 };
-                Some(pos)
+                return Some((current_row, col));
             }
 
             ScanState::Selected { .. } => {
                 // Already selected, restart scanning
                 self.start();
-                None
+                return None;
             }
+        };
+
+        if let Some(state) = next {
+            self.state = state;
         }
+        None
     }
 
     /// Handle a secondary switch press (usually to go back/cancel).
     pub fn on_secondary_switch(&mut self) {
         match self.state {
-            ScanState::ScanningColumns { .. } | ScanState::ScanningRowsInCol { .. } => {
-                // Go back to row/column scanning
+            ScanState::ScanningColumns { .. }
+            | ScanState::ScanningRowsInCol { .. }
+            | ScanState::ScanningBlockRows { .. } => {
+                // Go back to row/column/block scanning
                 self.start();
             }
+            ScanState::ScanningBlockCells { block, .. } => {
+                // Go back up to the rows of this block, or to blocks if
+                // the block has a single row
+                match self.block(block) {
+                    Some(b) if self.rows_in(b).len() > 1 => {
+                        self.state = ScanState::ScanningBlockRows {
+                            block,
+                            current_row: self.rows_in(b)[0],
+                        };
+                    }
+                    _ => self.start(),
+                }
+            }
             ScanState::Selected { .. } => {
                 // Cancel selection, restart
                 self.start();
@@ -298,113 +684,310 @@ impl Scanner {
     ///
     /// Returns `false` if max cycles reached and scanning should stop.
     pub fn advance(&mut self) -> bool {
+        if matches!(self.state, ScanState::Idle | ScanState::Selected { .. }) {
+            // No-op
+            return true;
+        }
+        let next = self.neighbor(true);
+
+        if let Some(state) = next {
+            self.state = state;
+            return true;
+        }
+
+        // Wrapped around
+        self.cycle_count += 1;
+        let max_reached = self.cycle_count >= self.config.max_cycles;
+        if self.is_top_level() {
+            if max_reached {
+                self.stop();
+                return false;
+            }
+            self.state = self.first_state().unwrap_or(ScanState::Idle);
+        } else if max_reached {
+            // Go back to the top level
+            self.cycle_count = 0;
+            self.state = self.first_state().unwrap_or(ScanState::Idle);
+        } else {
+            self.state = self
+                .restart_level()
+                .or_else(|| self.first_state())
+                .unwrap_or(ScanState::Idle);
+        }
+        true
+    }
+
+    /// Move the highlight back one item, wrapping to the last.
+    ///
+    /// Does not count towards `max_cycles`.
+    pub fn retreat(&mut self) {
+        if let Some(state) = self.neighbor(false) {
+            self.state = state;
+        }
+    }
+
+    /// The next (or previous) item at the current level.
+    ///
+    /// Moving forward returns `None` on wrap-around; moving backward wraps
+    /// to the last item.
+    fn neighbor(&self, forward: bool) -> Option<ScanState> {
+        let grid = self.grid();
         match self.state {
             ScanState::ScanningRows { current_row } => {
-                let next_row = current_row + 1;
-                if next_row >= self.rows {
-                    self.cycle_count += 1;
-                    if self.cycle_count >= self.config.max_cycles {
-                        self.stop();
-                        return false;
-                    }
-                    self.state = ScanState::ScanningRows { current_row: 0 };
-                } else {
-                    self.state = ScanState::ScanningRows {
-                        current_row: next_row,
-                    };
-                }
+                step(&self.rows_in(grid), current_row, forward)
+                    .map(|current_row| ScanState::ScanningRows { current_row })
             }
 
             ScanState::ScanningColumns { row, current_col } => {
-                let next_col = current_col + 1;
-                if next_col >= self.cols {
-                    self.cycle_count += 1;
-                    if self.cycle_count >= self.config.max_cycles {
-                        // Go back to row scanning
-                        self.cycle_count = 0;
-                        self.state = ScanState::ScanningRows { current_row: 0 };
-                        return true;
-                    }
-                    self.state = ScanState::ScanningColumns {
-                        row,
-                        current_col: 0,
-                    };
-                } else {
-                    self.state = ScanState::ScanningColumns {
-                        row,
-                        current_col: next_col,
-                    };
-                }
+                step(&self.cols_in_row(grid, row), current_col, forward)
+                    .map(|current_col| ScanState::ScanningColumns { row, current_col })
             }
 
-            ScanState::ScanningLinear { row, col } => {
-                let (next_row, next_col) = if col + 1 < self.cols {
-                    (row, col + 1)
-                } else if row + 1 < self.rows {
-                    (row + 1, 0)
-                } else {
-                    self.cycle_count += 1;
-                    if self.cycle_count >= self.config.max_cycles {
-                        self.stop();
-                        return false;
-                    }
-                    (0, 0)
-                };
-                self.state = ScanState::ScanningLinear {
-                    row: next_row,
-                    col: next_col,
-                };
-            }
+            ScanState::ScanningLinear { row, col } => step(&self.cells(), (row, col), forward)
+                .map(|(row, col)| ScanState::ScanningLinear { row, col }),
 
             ScanState::ScanningCols { current_col } => {
-                let next_col = current_col + 1;
-                if next_col >= self.cols {
-                    self.cycle_count += 1;
-                    if self.cycle_count >= self.config.max_cycles {
-                        self.stop();
-                        return false;
-                    }
-                    self.state = ScanState::ScanningCols { current_col: 0 };
-                } else {
-                    self.state = ScanState::ScanningCols {
-                        current_col: next_col,
-                    };
-                }
+                step(&self.cols_in(grid), current_col, forward)
+                    .map(|current_col| ScanState::ScanningCols { current_col })
             }
 
             ScanState::ScanningRowsInCol { col, current_row } => {
-                let next_row = current_row + 1;
-                if next_row >= self.rows {
-                    self.cycle_count += 1;
-                    if self.cycle_count >= self.config.max_cycles {
-                        self.cycle_count = 0;
-                        self.state = ScanState::ScanningCols { current_col: 0 };
-                        return true;
-                    }
-                    self.state = ScanState::ScanningRowsInCol {
-                        col,
-                        current_row: 0,
-                    };
-                } else {
-                    self.state = ScanState::ScanningRowsInCol {
-                        col,
-                        current_row: next_row,
-                    };
-                }
+                step(&self.rows_in_col(grid, col), current_row, forward)
+                    .map(|current_row| ScanState::ScanningRowsInCol { col, current_row })
             }
 
-            ScanState::Idle | ScanState::Selected { .. } => {
-                // No-op
+            ScanState::ScanningBlocks { current_block } => {
+                step(&self.scannable_blocks(), current_block, forward)
+                    .map(|current_block| ScanState::ScanningBlocks { current_block })
             }
+
+            ScanState::ScanningBlockRows { block, current_row } => self
+                .block(block)
+                .and_then(|b| step(&self.rows_in(b), current_row, forward))
+                .map(|current_row| ScanState::ScanningBlockRows { block, current_row }),
+
+            ScanState::ScanningBlockCells {
+                block,
+                row,
+                current_col,
+            } => self
+                .block(block)
+                .and_then(|b| step(&self.cols_in_row(b, row), current_col, forward))
+                .map(|current_col| ScanState::ScanningBlockCells {
+                    block,
+                    row,
+                    current_col,
+                }),
+
+            ScanState::Idle | ScanState::Selected { .. } => None,
         }
+    }
 
-        true
+    /// The first state for the current mode, or `None` if nothing is scannable.
+    fn first_state(&self) -> Option<ScanState> {
+        let grid = self.grid();
+        match self.mode {
+            ScanMode::RowColumn => self
+                .rows_in(grid)
+                .first()
+                .map(|&current_row| ScanState::ScanningRows { current_row }),
+            ScanMode::Linear => self
+                .cells()
+                .first()
+                .map(|&(row, col)| ScanState::ScanningLinear { row, col }),
+            ScanMode::ColumnRow => self
+                .cols_in(grid)
+                .first()
+                .map(|&current_col| ScanState::ScanningCols { current_col }),
+            ScanMode::Block => self
+                .scannable_blocks()
+                .first()
+                .map(|&current_block| ScanState::ScanningBlocks { current_block }),
+        }
+    }
+
+    /// The first state of the current nested level, for wrapping around.
+    fn restart_level(&self) -> Option<ScanState> {
+        match self.state {
+            ScanState::ScanningColumns { row, .. } => self.enter_row(self.grid(), row),
+            ScanState::ScanningRowsInCol { col, .. } => self.enter_col(col),
+            ScanState::ScanningBlockRows { block, .. } => {
+                let b = self.block(block)?;
+                let current_row = *self.rows_in(b).first()?;
+                Some(ScanState::ScanningBlockRows { block, current_row })
+            }
+            ScanState::ScanningBlockCells { block, row, .. } => {
+                self.enter_block_row(block, self.block(block)?, row)
+            }
+            _ => self.first_state(),
+        }
+    }
+
+    fn is_top_level(&self) -> bool {
+        matches!(
+            self.state,
+            ScanState::ScanningRows { .. }
+                | ScanState::ScanningLinear { .. }
+                | ScanState::ScanningCols { .. }
+                | ScanState::ScanningBlocks { .. }
+        )
+    }
+
+    fn enter_row(&self, area: ScanBlock, row: usize) -> Option<ScanState> {
+        let current_col = *self.cols_in_row(area, row).first()?;
+        Some(ScanState::ScanningColumns { row, current_col })
+    }
+
+    fn enter_col(&self, col: usize) -> Option<ScanState> {
+        let current_row = *self.rows_in_col(self.grid(), col).first()?;
+        Some(ScanState::ScanningRowsInCol { col, current_row })
+    }
+
+    fn enter_block(&self, index: usize) -> Option<ScanState> {
+        let block = self.block(index)?;
+        let rows = self.rows_in(block);
+        // Blocks with a single scannable row go straight to its cells
+        if let [row] = rows[..] {
+            return self.enter_block_row(index, block, row);
+        }
+        Some(ScanState::ScanningBlockRows {
+            block: index,
+            current_row: *rows.first()?,
+        })
+    }
+
+    fn enter_block_row(&self, index: usize, block: ScanBlock, row: usize) -> Option<ScanState> {
+        let current_col = *self.cols_in_row(block, row).first()?;
+        Some(ScanState::ScanningBlockCells {
+            block: index,
+            row,
+            current_col,
+        })
+    }
+
+    /// The whole grid as a block.
+    fn grid(&self) -> ScanBlock {
+        ScanBlock::new(0, 0, self.rows, self.cols)
+    }
+
+    /// Rows of an area that have at least one scannable cell, in scan order.
+    fn rows_in(&self, area: ScanBlock) -> Vec<usize> {
+        let rows = (area.row..area.row + area.rows)
+            .filter(|&row| !self.cols_in_row(area, row).is_empty())
+            .collect();
+        self.ordered(rows, |&row| {
+            let row_area = ScanBlock::new(row, area.col, 1, area.cols);
+            self.area_weight(row_area)
+        })
+    }
+
+    /// Columns of an area that have at least one scannable cell, in scan order.
+    fn cols_in(&self, area: ScanBlock) -> Vec<usize> {
+        let cols = (area.col..area.col + area.cols)
+            .filter(|&col| !self.rows_in_col(area, col).is_empty())
+            .collect();
+        self.ordered(cols, |&col| {
+            let col_area = ScanBlock::new(area.row, col, area.rows, 1);
+            self.area_weight(col_area)
+        })
+    }
+
+    /// Scannable columns in one row of an area, in scan order.
+    fn cols_in_row(&self, area: ScanBlock, row: usize) -> Vec<usize> {
+        let cols = (area.col..area.col + area.cols)
+            .filter(|&col| self.is_scannable(row, col))
+            .collect();
+        self.ordered(cols, |&col| self.weight(row, col))
+    }
+
+    /// Scannable rows in one column of an area, in scan order.
+    fn rows_in_col(&self, area: ScanBlock, col: usize) -> Vec<usize> {
+        let rows = (area.row..area.row + area.rows)
+            .filter(|&row| self.is_scannable(row, col))
+            .collect();
+        self.ordered(rows, |&row| self.weight(row, col))
+    }
+
+    /// All scannable positions, in scan order.
+    fn cells(&self) -> Vec<(usize, usize)> {
+        let cells = (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.is_scannable(row, col))
+            .collect();
+        self.ordered(cells, |&(row, col)| self.weight(row, col))
+    }
+
+    /// Indices of blocks that have at least one scannable cell, in scan order.
+    fn scannable_blocks(&self) -> Vec<usize> {
+        let blocks = self.blocks();
+        let indices = blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| !self.rows_in(**block).is_empty())
+            .map(|(index, _)| index)
+            .collect();
+        self.ordered(indices, |&index| self.area_weight(blocks[index]))
+    }
+
+    /// Sort items by descending weight for frequency ordering.
+    ///
+    /// The sort is stable, so ties keep grid order.
+    fn ordered<T>(&self, mut items: Vec<T>, weight: impl Fn(&T) -> i64) -> Vec<T> {
+        if self.config.order == ScanOrder::Frequency && self.weights.is_some() {
+            items.sort_by_key(|item| std::cmp::Reverse(weight(item)));
+        }
+        items
+    }
+
+    /// Weight of a single cell.
+    fn weight(&self, row: usize, col: usize) -> i64 {
+        self.weights
+            .as_ref()
+            .and_then(|w| w.get(row))
+            .and_then(|r| r.get(col))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Total weight of the scannable cells in an area.
+    fn area_weight(&self, area: ScanBlock) -> i64 {
+        (area.row..area.row + area.rows)
+            .flat_map(|row| (area.col..area.col + area.cols).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.is_scannable(row, col))
+            .map(|(row, col)| self.weight(row, col))
+            .sum()
+    }
+}
+
+/// The item after `current` in scan order, or `None` on wrap-around.
+///
+/// Moving backward wraps to the last item instead. If `current` is no
+/// longer in the list, moving forward wraps and moving backward goes to
+/// the last item.
+fn step<T: PartialEq + Copy>(items: &[T], current: T, forward: bool) -> Option<T> {
+    let index = items.iter().position(|&item| item == current);
+    if forward {
+        index.and_then(|i| items.get(i + 1)).copied()
+    } else {
+        match index {
+            Some(i) if i > 0 => items.get(i - 1).copied(),
+            _ => items.last().copied(),
+        }
+    }
+}
+
+/// Cut or pad a `[row][col]` grid to `rows` by `cols`.
+fn resize_grid<T: Clone>(grid: &mut Vec<Vec<T>>, rows: usize, cols: usize, fill: T) {
+    grid.resize(rows, Vec::new());
+    for row in grid.iter_mut() {
+        row.resize(cols, fill.clone());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::obf::ObfButton;
 
     #[test]
     fn test_row_column_scanning() {
@@ -502,6 +1085,306 @@ mod tests {
         assert!(matches!(scanner.state(), ScanState::ScanningRows { .. }));
     }
 
+    #[test]
+    fn test_quadrants() {
+        assert_eq!(
+            ScanBlock::quadrants(6, 8),
+            vec![
+                ScanBlock::new(0, 0, 3, 4),
+                ScanBlock::new(0, 4, 3, 4),
+                ScanBlock::new(3, 0, 3, 4),
+                ScanBlock::new(3, 4, 3, 4),
+            ]
+        );
+
+        // Odd sizes favor the top/left quadrants
+        let blocks = ScanBlock::quadrants(3, 5);
+        assert_eq!(blocks[0], ScanBlock::new(0, 0, 2, 3));
+        assert_eq!(blocks[3], ScanBlock::new(2, 3, 1, 2));
+
+        // A single row splits into halves
+        assert_eq!(ScanBlock::quadrants(1, 4).len(), 2);
+
+        // Empty blocks don't underflow, and the scanner drops them
+        let empty = ScanBlock::new(2, 3, 0, 0);
+        assert_eq!((empty.last_row(), empty.last_col()), (2, 3));
+        let scanner = Scanner::new(4, 4).with_blocks(vec![empty, ScanBlock::new(0, 0, 1, 1)]);
+        assert_eq!(scanner.blocks(), vec![ScanBlock::new(0, 0, 1, 1)]);
+    }
+
+    #[test]
+    fn test_block_scanning() {
+        let mut scanner = Scanner::new(6, 8).with_mode(ScanMode::Block);
+        scanner.start();
+        assert_eq!(
+            scanner.state(),
+            ScanState::ScanningBlocks { current_block: 0 }
+        );
+
+        // Pick the bottom-right quadrant
+        for _ in 0..3 {
+            scanner.advance();
+        }
+        assert_eq!(scanner.state().highlighted_block(), Some(3));
+        scanner.on_switch_press();
+        assert_eq!(
+            scanner.state(),
+            ScanState::ScanningBlockRows {
+                block: 3,
+                current_row: 3
+            }
+        );
+
+        // Pick the second row of the block
+        scanner.advance();
+        scanner.on_switch_press();
+        assert_eq!(
+            scanner.state(),
+            ScanState::ScanningBlockCells {
+                block: 3,
+                row: 4,
+                current_col: 4
+            }
+        );
+
+        // Cells wrap within the block
+        for _ in 0..4 {
+            scanner.advance();
+        }
+        assert_eq!(scanner.state().highlighted_position(), Some((4, 4)));
+
+        scanner.advance();
+        assert_eq!(scanner.on_switch_press(), Some((4, 5)));
+    }
+
+    #[test]
+    fn test_board_defined_blocks() {
+        let mut scanner = Scanner::new(3, 4)
+            .with_mode(ScanMode::Block)
+            .with_blocks(vec![ScanBlock::new(0, 0, 1, 4), ScanBlock::new(1, 0, 5, 4)]);
+
+        // Blocks are clipped to the grid
+        assert_eq!(scanner.blocks()[1], ScanBlock::new(1, 0, 2, 4));
+
+        // Single-row blocks skip row scanning
+        scanner.start();
+        scanner.on_switch_press();
+        assert_eq!(
+            scanner.state(),
+            ScanState::ScanningBlockCells {
+                block: 0,
+                row: 0,
+                current_col: 0
+            }
+        );
+
+        // Secondary switch goes back to blocks
+        scanner.on_secondary_switch();
+        assert_eq!(
+            scanner.state(),
+            ScanState::ScanningBlocks { current_block: 0 }
+        );
+
+        // Changing boards drops the blocks
+        scanner.set_grid_size(2, 2);
+        assert_eq!(scanner.blocks(), ScanBlock::quadrants(2, 2));
+    }
+
+    #[test]
+    fn test_resize_keeps_occupancy() {
+        // . a
+        // b .
+        let mut scanner = Scanner::from_occupancy(vec![vec![false, true], vec![true, false]])
+            .with_mode(ScanMode::Linear);
+
+        // New positions are empty, so the scan still only stops on a and b
+        scanner.set_grid_size(3, 3);
+        assert!(scanner.is_scannable(0, 1));
+        assert!(!scanner.is_scannable(2, 2));
+        scanner.start();
+        assert_eq!(scanner.state().highlighted_position(), Some((0, 1)));
+        scanner.advance();
+        assert_eq!(scanner.state().highlighted_position(), Some((1, 0)));
+        scanner.advance();
+        assert_eq!(scanner.state().highlighted_position(), Some((0, 1)));
+
+        // Shrinking cuts the mask
+        scanner.set_grid_size(1, 1);
+        assert!(!scanner.is_scannable(0, 0));
+    }
+
+    fn sparse_board() -> Board {
+        // . a . .
+        // . . . .
+        // b . h c     (h is hidden)
+        let mut board = Board::new("sparse", 3, 4);
+        board.add_cell(ObfButton::new("a", "A"), 0, 1).unwrap();
+        board.add_cell(ObfButton::new("b", "B"), 2, 0).unwrap();
+        let hidden = ObfButton {
+            hidden: true,
+            ..ObfButton::new("h", "H")
+        };
+        board.add_cell(hidden, 2, 2).unwrap();
+        board.add_cell(ObfButton::new("c", "C"), 2, 3).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_row_column_skips_empty() {
+        let mut scanner = Scanner::from_board(&sparse_board());
+        scanner.start();
+        assert_eq!(scanner.state(), ScanState::ScanningRows { current_row: 0 });
+
+        // The empty middle row is skipped
+        scanner.advance();
+        assert_eq!(scanner.state(), ScanState::ScanningRows { current_row: 2 });
+
+        // Columns skip the empty and hidden cells
+        scanner.on_switch_press();
+        assert_eq!(scanner.state().highlighted_position(), Some((2, 0)));
+        scanner.advance();
+        assert_eq!(scanner.state().highlighted_position(), Some((2, 3)));
+        assert_eq!(scanner.on_switch_press(), Some((2, 3)));
+    }
+
+    #[test]
+    fn test_linear_and_column_row_skip_empty() {
+        let board = sparse_board();
+        let mut linear = Scanner::from_board(&board).with_mode(ScanMode::Linear);
+        linear.start();
+        let mut visited = vec![linear.state().highlighted_position().unwrap()];
+        for _ in 0..2 {
+            linear.advance();
+            visited.push(linear.state().highlighted_position().unwrap());
+        }
+        assert_eq!(visited, vec![(0, 1), (2, 0), (2, 3)]);
+
+        let mut columns = Scanner::from_board(&board).with_mode(ScanMode::ColumnRow);
+        columns.start();
+        let mut cols = vec![];
+        for _ in 0..3 {
+            if let ScanState::ScanningCols { current_col } = columns.state() {
+                cols.push(current_col);
+            }
+            columns.advance();
+        }
+        assert_eq!(cols, vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_skip_empty_disabled() {
+        let config = ScanConfig {
+            skip_empty: false,
+            ..Default::default()
+        };
+        let mut scanner = Scanner::from_board(&sparse_board()).with_config(config);
+        scanner.start();
+        scanner.advance();
+        assert_eq!(scanner.state(), ScanState::ScanningRows { current_row: 1 });
+    }
+
+    #[test]
+    fn test_occupancy_mask() {
+        let mut scanner = Scanner::from_occupancy(vec![vec![false, false], vec![false, true]])
+            .with_mode(ScanMode::Block);
+        assert!(!scanner.is_scannable(0, 0));
+        assert!(scanner.is_scannable(1, 1));
+
+        // Only the bottom-right quadrant has anything to select
+        scanner.start();
+        assert_eq!(scanner.state().highlighted_block(), Some(3));
+        scanner.on_switch_press();
+        assert_eq!(scanner.on_switch_press(), Some((1, 1)));
+
+        // An empty grid has nothing to scan
+        let mut empty = Scanner::from_occupancy(vec![vec![false]]);
+        empty.start();
+        assert_eq!(empty.state(), ScanState::Idle);
+    }
+
+    #[test]
+    fn test_retreat_wraps() {
+        let mut scanner = Scanner::new(3, 2);
+        scanner.start();
+        scanner.retreat();
+        assert_eq!(scanner.state(), ScanState::ScanningRows { current_row: 2 });
+        assert_eq!(scanner.cycle_count(), 0);
+
+        scanner.on_switch_press();
+        scanner.retreat();
+        assert_eq!(scanner.state().highlighted_position(), Some((2, 1)));
+        scanner.retreat();
+        assert_eq!(scanner.state().highlighted_position(), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_announcements() {
+        let mut scanner = Scanner::new(6, 8).with_mode(ScanMode::Block);
+        assert_eq!(scanner.announcement(), None);
+
+        scanner.start();
+        scanner.advance();
+        let block = scanner.announcement().unwrap();
+        assert_eq!(block.level, ScanLevel::Block);
+        assert_eq!(block.index, 1);
+        assert_eq!(block.area, ScanBlock::new(0, 4, 3, 4));
+
+        // Rows inside a block only cover the block's columns
+        scanner.on_switch_press();
+        let row = scanner.announcement().unwrap();
+        assert_eq!(row.level, ScanLevel::Row);
+        assert_eq!(row.area, ScanBlock::new(0, 4, 1, 4));
+
+        scanner.on_switch_press();
+        let cell = scanner.announcement().unwrap();
+        assert_eq!(cell.level, ScanLevel::Cell);
+        assert_eq!(cell.area, ScanBlock::new(0, 4, 1, 1));
+
+        scanner.on_switch_press();
+        assert_eq!(scanner.announcement(), None);
+    }
+
+    #[test]
+    fn test_frequency_order() {
+        let weights = vec![vec![0, 1, 0], vec![0, 0, 9]];
+        let config = ScanConfig {
+            order: ScanOrder::Frequency,
+            ..Default::default()
+        };
+
+        // Linear: heaviest first, ties in grid order
+        let mut linear = Scanner::new(2, 3)
+            .with_mode(ScanMode::Linear)
+            .with_config(config.clone())
+            .with_weights(weights.clone());
+        linear.start();
+        let mut visited = vec![];
+        for _ in 0..6 {
+            visited.push(linear.state().highlighted_position().unwrap());
+            linear.advance();
+        }
+        assert_eq!(
+            visited,
+            vec![(1, 2), (0, 1), (0, 0), (0, 2), (1, 0), (1, 1)]
+        );
+
+        // Row-column: the row with the most weight comes first
+        let mut rows = Scanner::new(2, 3)
+            .with_config(config)
+            .with_weights(weights.clone());
+        rows.start();
+        assert_eq!(rows.state(), ScanState::ScanningRows { current_row: 1 });
+        rows.on_switch_press();
+        assert_eq!(rows.state().highlighted_position(), Some((1, 2)));
+
+        // Grid order ignores weights
+        let mut grid = Scanner::new(2, 3)
+            .with_mode(ScanMode::Linear)
+            .with_weights(weights);
+        grid.start();
+        assert_eq!(grid.state().highlighted_position(), Some((0, 0)));
+    }
+
     #[test]
     fn test_highlighted_position() {
         let mut scanner = Scanner::new(2, 3);
@@ -0,0 +1,250 @@
+//! Voice command grammar.
+//!
+//! Speech recognizers hand over plain text in [`InputEvent::Voice`]. A
+//! [`VoiceGrammar`] turns those phrases into [`VoiceCommand`]s and matches
+//! spoken labels and board names fuzzily, so recognizers tuned for
+//! dysarthric speech that return "helo" or "go bak" still work.
+//!
+//! | Phrase                                     | Command                   |
+//! |--------------------------------------------|---------------------------|
+//! | "back", "go back"                          | [`VoiceCommand::Back`]    |
+//! | "home", "go home"                          | [`VoiceCommand::Home`]    |
+//! | "clear", "clear message"                   | [`VoiceCommand::Clear`]   |
+//! | "speak", "speak message", "say it"         | [`VoiceCommand::Speak`]   |
+//! | "say \<label\>", "select \<label\>"        | [`VoiceCommand::Say`]     |
+//! | "open \<board\>", "go to \<board\>"        | [`VoiceCommand::Open`]    |
+//!
+//! # Example
+//!
+//! ```rust
+//! use lovewords_core::input::{VoiceCommand, VoiceGrammar};
+//!
+//! let grammar = VoiceGrammar::default();
+//! assert_eq!(grammar.parse("Go bak"), Some(VoiceCommand::Back));
+//! assert_eq!(
+//!     grammar.parse("say I love you!"),
+//!     Some(VoiceCommand::Say("i love you".to_string()))
+//! );
+//!
+//! let labels = [("Hello", 0), ("Goodbye", 1)];
+//! assert_eq!(grammar.best_match("helo", labels), Some(0));
+//! ```
+//!
+//! [`InputEvent::Voice`]: super::InputEvent::Voice
+
+/// A command recognized from a spoken phrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoiceCommand {
+    /// Go back to the previous board.
+    Back,
+    /// Go to the home board.
+    Home,
+    /// Clear the message bar.
+    Clear,
+    /// Speak the message bar.
+    Speak,
+    /// Activate the cell with this label on the current board.
+    Say(String),
+    /// Open the board with this name.
+    Open(String),
+}
+
+/// Fixed phrases, checked before the `say` and `open` prefixes.
+const PHRASES: &[(&str, VoiceCommand)] = &[
+    ("back", VoiceCommand::Back),
+    ("go back", VoiceCommand::Back),
+    ("home", VoiceCommand::Home),
+    ("go home", VoiceCommand::Home),
+    ("clear", VoiceCommand::Clear),
+    ("clear message", VoiceCommand::Clear),
+    ("speak", VoiceCommand::Speak),
+    ("speak message", VoiceCommand::Speak),
+    ("say it", VoiceCommand::Speak),
+];
+
+/// Prefixes that take a label.
+const SAY: &[&str] = &["say", "select", "press"];
+
+/// Prefixes that take a board name.
+const OPEN: &[&str] = &["open", "go to", "show"];
+
+/// Default similarity needed for a fuzzy match.
+pub const DEFAULT_VOICE_THRESHOLD: f32 = 0.75;
+
+/// Parser for voice commands, with fuzzy matching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceGrammar {
+    threshold: f32,
+}
+
+impl Default for VoiceGrammar {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_VOICE_THRESHOLD,
+        }
+    }
+}
+
+impl VoiceGrammar {
+    /// Create a grammar with the default threshold.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the similarity (0.0 to 1.0) needed for a fuzzy match.
+    ///
+    /// Lower values tolerate more recognition errors but pick the wrong
+    /// cell more often.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the similarity needed for a fuzzy match.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Parse a recognized phrase.
+    ///
+    /// Returns `None` if the phrase isn't a command.
+    pub fn parse(&self, phrase: &str) -> Option<VoiceCommand> {
+        let phrase = normalize(phrase);
+        if phrase.is_empty() {
+            return None;
+        }
+
+        let prefixed = |prefixes: &[&str]| {
+            prefixes.iter().find_map(|prefix| {
+                let rest = phrase.strip_prefix(prefix)?.strip_prefix(' ')?;
+                Some(rest.to_string())
+            })
+        };
+        let exact = PHRASES.iter().find(|(p, _)| *p == phrase);
+        if let Some((_, command)) = exact {
+            return Some(command.clone());
+        }
+        if let Some(label) = prefixed(SAY) {
+            return Some(VoiceCommand::Say(label));
+        }
+        if let Some(name) = prefixed(OPEN) {
+            return Some(VoiceCommand::Open(name));
+        }
+        self.best_match(&phrase, PHRASES.iter().map(|(p, c)| (*p, c.clone())))
+    }
+
+    /// Find the candidate whose text best matches a spoken phrase.
+    ///
+    /// Matching ignores case and punctuation. Returns `None` if no
+    /// candidate is similar enough.
+    pub fn best_match<S, T, I>(&self, phrase: &str, candidates: I) -> Option<T>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = (S, T)>,
+    {
+        let phrase = normalize(phrase);
+        let mut best: Option<(f32, T)> = None;
+        for (text, value) in candidates {
+            let score = similarity(&phrase, &normalize(text.as_ref()));
+            if score >= self.threshold && best.as_ref().is_none_or(|(b, _)| score > *b) {
+                best = Some((score, value));
+            }
+        }
+        best.map(|(_, value)| value)
+    }
+}
+
+/// Similarity of two strings, from 0.0 (nothing alike) to 1.0 (equal),
+/// based on edit distance.
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f32 / len as f32
+}
+
+/// Levenshtein distance, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Lowercase, drop punctuation and collapse whitespace.
+fn normalize(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .to_lowercase();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_phrases() {
+        let grammar = VoiceGrammar::new();
+        assert_eq!(grammar.parse("Go back."), Some(VoiceCommand::Back));
+        assert_eq!(grammar.parse("HOME"), Some(VoiceCommand::Home));
+        assert_eq!(grammar.parse("clear message"), Some(VoiceCommand::Clear));
+        assert_eq!(grammar.parse("say it"), Some(VoiceCommand::Speak));
+        assert_eq!(
+            grammar.parse("select  Thank you"),
+            Some(VoiceCommand::Say("thank you".into()))
+        );
+        assert_eq!(
+            grammar.parse("go to feelings"),
+            Some(VoiceCommand::Open("feelings".into()))
+        );
+        assert_eq!(grammar.parse("what time is it"), None);
+        assert_eq!(grammar.parse("  "), None);
+    }
+
+    #[test]
+    fn test_fuzzy_phrases() {
+        let grammar = VoiceGrammar::new();
+        assert_eq!(grammar.parse("clea"), Some(VoiceCommand::Clear));
+        assert_eq!(grammar.parse("go hom"), Some(VoiceCommand::Home));
+        assert_eq!(grammar.parse("speek"), Some(VoiceCommand::Speak));
+    }
+
+    #[test]
+    fn test_best_match() {
+        let grammar = VoiceGrammar::new();
+        let labels = vec![("I love you", 'a'), ("I miss you", 'b'), ("Hug", 'c')];
+        assert_eq!(grammar.best_match("i love yu", labels.clone()), Some('a'));
+        assert_eq!(grammar.best_match("I miss you!", labels.clone()), Some('b'));
+        assert_eq!(grammar.best_match("dog", labels.clone()), None);
+
+        // A lower threshold accepts worse recognitions
+        let loose = VoiceGrammar::new().with_threshold(0.3);
+        assert_eq!(loose.best_match("hog", labels), Some('c'));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("hello", "hello"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert!(similarity("helo", "hello") >= 0.8);
+    }
+}
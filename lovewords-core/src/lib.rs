@@ -8,6 +8,7 @@
 //! - **Speech Abstraction**: Platform-agnostic TTS trait
 //! - **Storage Abstraction**: Flexible persistence backends
 //! - **Input Handling**: Support for touch, switch scanning, and dwell selection
+//! - **Sessions**: Input dispatch that drives navigation, speech and the message bar
 //!
 //! ## Quick Start
 //!
@@ -30,6 +31,7 @@ pub mod board;
 pub mod error;
 pub mod input;
 pub mod obf;
+pub mod session;
 pub mod speech;
 pub mod storage;
 
@@ -38,5 +40,6 @@ pub use board::{Board, BoardNavigator, Cell, CellAction};
 pub use error::{LoveWordsError, Result};
 pub use input::{InputEvent, ScanMode, Scanner};
 pub use obf::{ObfBoard, ObfButton, ObfExtensions, ObfGrid, ObfImage, ObfLoadBoard};
+pub use session::{Session, SessionEvent};
 pub use speech::{SpeechEngine, Voice, VoiceConfig};
 pub use storage::{MemoryStorage, Profile, ProfileSettings, StorageBackend};
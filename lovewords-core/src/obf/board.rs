@@ -6,6 +6,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::extensions::ObfExtensions;
+use super::validate::{self, Diagnostic};
 
 /// The format string for OBF v1.0.
 pub const OBF_FORMAT: &str = "open-board-0.1";
@@ -77,7 +78,7 @@ pub struct ObfBoard {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<ObfLicense>,
 
-    /// LoveWords-specific extensions.
+    /// LoveWords-specific extensions, plus any unrecognized keys.
     #[serde(flatten, default)]
     pub extensions: ObfExtensions,
 }
@@ -101,6 +102,13 @@ impl ObfBoard {
         }
     }
 
+    /// Check the board for structural problems.
+    ///
+    /// See [`validate`](super::validate) for details.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(self)
+    }
+
     /// Get a button by its ID.
     pub fn button(&self, id: &str) -> Option<&ObfButton> {
         self.buttons.iter().find(|b| b.id == id)
@@ -120,6 +128,17 @@ impl ObfBoard {
         self.button_id_at(row, col).and_then(|id| self.button(id))
     }
 
+    /// Get a `[row][col]` mask of positions holding a visible button.
+    pub fn occupancy(&self) -> Vec<Vec<bool>> {
+        (0..self.grid.rows)
+            .map(|row| {
+                (0..self.grid.columns)
+                    .map(|col| self.button_at(row, col).is_some_and(|b| !b.hidden))
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Add a button to the board.
     pub fn add_button(&mut self, button: ObfButton) {
         self.buttons.push(button);
@@ -169,6 +188,10 @@ pub struct ObfButton {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action: Option<String>,
 
+    /// Ordered list of actions; takes precedence over `action` when set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+
     /// Board to load when pressed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub load_board: Option<ObfLoadBoard>,
@@ -185,7 +208,7 @@ pub struct ObfButton {
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub hidden: bool,
 
-    /// LoveWords-specific extensions for this button.
+    /// LoveWords-specific extensions for this button, plus any unrecognized keys.
     #[serde(flatten, default)]
     pub extensions: ObfExtensions,
 }
@@ -200,6 +223,7 @@ impl ObfButton {
             image_id: None,
             sound_id: None,
             action: None,
+            actions: Vec::new(),
             load_board: None,
             background_color: None,
             border_color: None,
@@ -259,6 +283,12 @@ impl ObfButton {
         self
     }
 
+    /// Add an action to the ordered action list.
+    pub fn with_action(mut self, action: impl Into<String>) -> Self {
+        self.actions.push(action.into());
+        self
+    }
+
     /// Get the text to speak for this button.
     ///
     /// Returns vocalization if set, otherwise falls back to label.
@@ -323,7 +353,7 @@ pub struct ObfLoadBoard {
 }
 
 /// An image resource.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ObfImage {
     /// Unique identifier for this image.
     pub id: String,
@@ -359,10 +389,14 @@ pub struct ObfImage {
     /// License information for this image.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<ObfLicense>,
+
+    /// Extensions and unrecognized keys for this image.
+    #[serde(flatten, default)]
+    pub extensions: ObfExtensions,
 }
 
 /// A sound resource.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ObfSound {
     /// Unique identifier for this sound.
     pub id: String,
@@ -390,6 +424,10 @@ pub struct ObfSound {
     /// License information for this sound.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<ObfLicense>,
+
+    /// Extensions and unrecognized keys for this sound.
+    #[serde(flatten, default)]
+    pub extensions: ObfExtensions,
 }
 
 /// License information for OBF content.
@@ -449,6 +487,41 @@ mod tests {
         assert_eq!(board, parsed);
     }
 
+    #[test]
+    fn test_foreign_keys_roundtrip() {
+        let json = r#"{
+            "format": "open-board-0.1",
+            "id": "cd-board",
+            "name": "From CoughDrop",
+            "locale": "en",
+            "ext_coughdrop_board_id": "1_234",
+            "buttons": [
+                {"id": "b1", "label": "Hi", "ext_coughdrop_part_of_speech": "noun"}
+            ],
+            "images": [
+                {"id": "i1", "url": "https://example.com/i.png", "ext_coughdrop_hc": true}
+            ],
+            "sounds": [
+                {"id": "s1", "url": "https://example.com/s.mp3", "vendor_note": "x"}
+            ],
+            "grid": {"rows": 1, "columns": 1, "order": [["b1"]]}
+        }"#;
+
+        let board: ObfBoard = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            board.extensions.extension("coughdrop", "board_id"),
+            Some(&serde_json::Value::from("1_234"))
+        );
+        assert!(board.buttons[0]
+            .extensions
+            .extension("coughdrop", "part_of_speech")
+            .is_some());
+
+        let saved = serde_json::to_value(&board).unwrap();
+        let original: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(saved, original);
+    }
+
     #[test]
     fn test_obf_grid_navigation() {
         let mut board = ObfBoard::new("nav-test", 2, 3);
@@ -11,8 +11,31 @@
 //! - `ext_lovewords_intimacy_level`: Privacy/intimacy level (1-5 scale)
 //! - `ext_lovewords_partner_specific`: Whether this is specific to a partner relationship
 //! - `ext_lovewords_celebration`: Special occasion type
+//! - `ext_lovewords_scan_blocks`: Cell groups for block scanning
+//!
+//! Extensions from other vendors (e.g. `ext_coughdrop_*`) and any other keys
+//! this crate doesn't model are kept in [`ObfExtensions::extra_extensions`] so
+//! boards from other apps survive a load/save round-trip unchanged.
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A rectangular group of cells in `ext_lovewords_scan_blocks`.
+///
+/// See [`ScanBlock`](crate::input::ScanBlock) for how the scanner uses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObfScanBlock {
+    /// Top row of the block.
+    pub row: usize,
+    /// Left column of the block.
+    pub col: usize,
+    /// Number of rows in the block.
+    pub rows: usize,
+    /// Number of columns in the block.
+    pub cols: usize,
+}
 
 /// LoveWords-specific extensions for OBF boards and buttons.
 ///
@@ -83,6 +106,23 @@ pub struct ObfExtensions {
         skip_serializing_if = "Option::is_none"
     )]
     pub priority: Option<i32>,
+
+    /// Cell groups for block scanning, in scan order.
+    ///
+    /// Boards without this use quadrants of the grid.
+    #[serde(
+        rename = "ext_lovewords_scan_blocks",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scan_blocks: Option<Vec<ObfScanBlock>>,
+
+    /// Unrecognized keys, preserved verbatim.
+    ///
+    /// Holds foreign `ext_*` extensions as well as any OBF keys this crate
+    /// doesn't model yet. Use [`extension`](Self::extension) and
+    /// [`set_extension`](Self::set_extension) to work with them by namespace.
+    #[serde(flatten)]
+    pub extra_extensions: BTreeMap<String, Value>,
 }
 
 impl ObfExtensions {
@@ -137,9 +177,56 @@ impl ObfExtensions {
             && self.tags.is_none()
             && self.tone.is_none()
             && self.priority.is_none()
+            && self.scan_blocks.is_none()
+    }
+
+    /// Get a foreign extension value, e.g. `extension("coughdrop", "home_board")`
+    /// for the `ext_coughdrop_home_board` key.
+    pub fn extension(&self, namespace: &str, name: &str) -> Option<&Value> {
+        self.extra_extensions.get(&extension_key(namespace, name))
+    }
+
+    /// Set a foreign extension value, replacing any existing one.
+    pub fn set_extension(&mut self, namespace: &str, name: &str, value: impl Into<Value>) {
+        self.extra_extensions
+            .insert(extension_key(namespace, name), value.into());
+    }
+
+    /// Remove a foreign extension value, returning it if present.
+    pub fn remove_extension(&mut self, namespace: &str, name: &str) -> Option<Value> {
+        self.extra_extensions
+            .remove(&extension_key(namespace, name))
+    }
+
+    /// Iterate over all extension values in a namespace as `(name, value)` pairs.
+    pub fn extensions_in<'a>(
+        &'a self,
+        namespace: &str,
+    ) -> impl Iterator<Item = (&'a str, &'a Value)> + 'a {
+        let prefix = extension_key(namespace, "");
+        self.extra_extensions
+            .iter()
+            .filter_map(move |(key, value)| key.strip_prefix(&prefix).map(|name| (name, value)))
+    }
+
+    /// List the namespaces of all preserved `ext_*` keys.
+    pub fn namespaces(&self) -> Vec<&str> {
+        let mut namespaces: Vec<&str> = self
+            .extra_extensions
+            .keys()
+            .filter_map(|key| key.strip_prefix("ext_"))
+            .filter_map(|rest| rest.split('_').next())
+            .collect();
+        namespaces.dedup();
+        namespaces
     }
 }
 
+/// Build the OBF key for a namespaced extension.
+fn extension_key(namespace: &str, name: &str) -> String {
+    format!("ext_{}_{}", namespace, name)
+}
+
 /// Common warmth categories for LoveWords content.
 #[allow(dead_code)]
 pub mod warmth {
@@ -211,6 +298,65 @@ mod tests {
         assert!(!not_empty.is_empty());
     }
 
+    #[test]
+    fn test_unknown_keys_preserved() {
+        let json = r#"{
+            "ext_lovewords_moment": "bedtime",
+            "ext_coughdrop_home_board": true,
+            "ext_coughdrop_settings": {"word_suggestions": false},
+            "future_obf_key": [1, 2]
+        }"#;
+
+        let ext: ObfExtensions = serde_json::from_str(json).unwrap();
+        assert_eq!(ext.moment, Some("bedtime".to_string()));
+        assert_eq!(ext.extra_extensions.len(), 3);
+
+        let value = serde_json::to_value(&ext).unwrap();
+        let original: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn test_extension_namespace_access() {
+        let mut ext = ObfExtensions::default();
+        ext.set_extension("coughdrop", "home_board", true);
+        ext.set_extension("coughdrop", "level", 3);
+        ext.set_extension("acme", "color", "red");
+
+        assert_eq!(
+            ext.extension("coughdrop", "home_board"),
+            Some(&Value::Bool(true))
+        );
+        assert!(ext.extra_extensions.contains_key("ext_coughdrop_level"));
+        assert_eq!(ext.extensions_in("coughdrop").count(), 2);
+        assert_eq!(ext.namespaces(), vec!["acme", "coughdrop"]);
+
+        assert_eq!(
+            ext.remove_extension("acme", "color"),
+            Some(Value::from("red"))
+        );
+        assert!(ext.extension("acme", "color").is_none());
+
+        // Foreign extensions don't count as LoveWords extensions
+        assert!(ext.is_empty());
+    }
+
+    #[test]
+    fn test_scan_blocks_extension() {
+        let json = r#"{"ext_lovewords_scan_blocks": [{"row": 0, "col": 0, "rows": 2, "cols": 3}]}"#;
+        let ext: ObfExtensions = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            ext.scan_blocks,
+            Some(vec![ObfScanBlock {
+                row: 0,
+                col: 0,
+                rows: 2,
+                cols: 3
+            }])
+        );
+        assert!(ext.extra_extensions.is_empty());
+    }
+
     #[test]
     fn test_intimacy_clamping() {
         let ext = ObfExtensions::default().with_intimacy(10);
@@ -7,6 +7,10 @@
 
 mod board;
 mod extensions;
+pub mod obz;
+mod validate;
 
 pub use board::{ObfBoard, ObfButton, ObfGrid, ObfImage, ObfLoadBoard, ObfSound};
-pub use extensions::ObfExtensions;
+pub use extensions::{ObfExtensions, ObfScanBlock};
+pub use obz::{ObzManifest, ObzPackage};
+pub use validate::{validate, Diagnostic, DiagnosticCode, Severity};
@@ -0,0 +1,680 @@
+//! OBZ package support for complete board sets.
+//!
+//! An OBZ file is a zip archive containing a `manifest.json`, one or more
+//! `.obf` boards, and the image and sound files they reference. This is the
+//! format CoughDrop and most other OBF tools use to exchange whole board sets.
+//!
+//! All paths inside a package are relative to the root of the archive.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lovewords_core::obf::ObzPackage;
+//! use lovewords_core::{ObfBoard, ObfButton};
+//!
+//! let mut home = ObfBoard::new("home", 1, 2);
+//! home.add_button(ObfButton::navigate("more", "More", "feelings"));
+//! home.place_button_at("more", 0, 0);
+//! let feelings = ObfBoard::new("feelings", 2, 2);
+//!
+//! let package = ObzPackage::new(home).with_board(feelings);
+//! let bytes = package.to_bytes().unwrap();
+//!
+//! let loaded = ObzPackage::from_bytes(&bytes).unwrap();
+//! assert_eq!(loaded.root_board().unwrap().id, "home");
+//! assert!(loaded.board("feelings").is_some());
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::{Cursor, Read, Seek, Write};
+
+use serde::{Deserialize, Serialize};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::board::{ObfBoard, OBF_FORMAT};
+use crate::error::ObfError;
+
+/// Name of the manifest entry at the root of every OBZ package.
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// The `manifest.json` of an OBZ package.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ObzManifest {
+    /// Format identifier, should be "open-board-0.1".
+    pub format: String,
+
+    /// Path to the root board within the package.
+    pub root: String,
+
+    /// Paths to every board, image and sound in the package.
+    #[serde(default)]
+    pub paths: ObzPaths,
+}
+
+/// Path tables from an OBZ manifest, keyed by resource ID.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ObzPaths {
+    /// Board ID to board path.
+    #[serde(default)]
+    pub boards: BTreeMap<String, String>,
+
+    /// Image ID to image path.
+    #[serde(default)]
+    pub images: BTreeMap<String, String>,
+
+    /// Sound ID to sound path.
+    #[serde(default)]
+    pub sounds: BTreeMap<String, String>,
+}
+
+/// A set of boards and their assets, read from or written to an OBZ file.
+///
+/// When reading, board links given only as `load_board.path` are resolved to
+/// board IDs, and the files behind `ObfImage::path` / `ObfSound::path` are
+/// loaded into [`images`](Self::images) and [`sounds`](Self::sounds) keyed by
+/// resource ID. When writing, the paths are regenerated from those IDs so the
+/// package is self-contained.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObzPackage {
+    /// ID of the root board.
+    pub root: String,
+
+    /// All boards in the package, root board first.
+    pub boards: Vec<ObfBoard>,
+
+    /// Image data keyed by image ID.
+    pub images: HashMap<String, Vec<u8>>,
+
+    /// Sound data keyed by sound ID.
+    pub sounds: HashMap<String, Vec<u8>>,
+}
+
+impl ObzPackage {
+    /// Create a package with the given root board.
+    pub fn new(root: ObfBoard) -> Self {
+        Self {
+            root: root.id.clone(),
+            boards: vec![root],
+            images: HashMap::new(),
+            sounds: HashMap::new(),
+        }
+    }
+
+    /// Add a board to the package.
+    pub fn with_board(mut self, board: ObfBoard) -> Self {
+        self.boards.push(board);
+        self
+    }
+
+    /// Attach image data for an image ID.
+    pub fn with_image(mut self, image_id: impl Into<String>, data: Vec<u8>) -> Self {
+        self.images.insert(image_id.into(), data);
+        self
+    }
+
+    /// Attach sound data for a sound ID.
+    pub fn with_sound(mut self, sound_id: impl Into<String>, data: Vec<u8>) -> Self {
+        self.sounds.insert(sound_id.into(), data);
+        self
+    }
+
+    /// Get the root board.
+    pub fn root_board(&self) -> Option<&ObfBoard> {
+        self.board(&self.root)
+    }
+
+    /// Get a board by its ID.
+    pub fn board(&self, id: &str) -> Option<&ObfBoard> {
+        self.boards.iter().find(|b| b.id == id)
+    }
+
+    /// Get the data for an image ID, if it was included in the package.
+    pub fn image_data(&self, image_id: &str) -> Option<&[u8]> {
+        self.images.get(image_id).map(Vec::as_slice)
+    }
+
+    /// Get the data for a sound ID, if it was included in the package.
+    pub fn sound_data(&self, sound_id: &str) -> Option<&[u8]> {
+        self.sounds.get(sound_id).map(Vec::as_slice)
+    }
+
+    /// Read a package from an in-memory OBZ file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ObfError> {
+        Self::read(Cursor::new(bytes))
+    }
+
+    /// Write the package to an in-memory OBZ file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ObfError> {
+        Ok(self.write(Cursor::new(Vec::new()))?.into_inner())
+    }
+
+    /// Read a package from an OBZ archive.
+    ///
+    /// Starting from the manifest's root, every board listed in the manifest
+    /// and every board reachable through `load_board.path` is loaded. Images
+    /// and sounds missing from the archive are skipped with a warning.
+    pub fn read<R: Read + Seek>(reader: R) -> Result<Self, ObfError> {
+        let mut archive = ZipArchive::new(reader)?;
+        let manifest: ObzManifest =
+            serde_json::from_slice(&read_entry(&mut archive, MANIFEST_PATH)?)?;
+
+        let mut queue: VecDeque<String> = VecDeque::new();
+        queue.push_back(normalize_path(&manifest.root));
+        queue.extend(manifest.paths.boards.values().map(|p| normalize_path(p)));
+
+        let mut seen = HashSet::new();
+        let mut boards = Vec::new();
+        let mut ids_by_path = HashMap::new();
+
+        while let Some(path) = queue.pop_front() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let board: ObfBoard = serde_json::from_slice(&read_entry(&mut archive, &path)?)?;
+            for button in &board.buttons {
+                if let Some(target) = button.load_board.as_ref().and_then(|l| l.path.as_ref()) {
+                    queue.push_back(normalize_path(target));
+                }
+            }
+            ids_by_path.insert(path, board.id.clone());
+            boards.push(board);
+        }
+
+        let root = ids_by_path
+            .get(&normalize_path(&manifest.root))
+            .cloned()
+            .ok_or_else(|| ObfError::MissingEntry(manifest.root.clone()))?;
+
+        // Fill in board IDs for links that only name a path
+        for board in &mut boards {
+            for button in &mut board.buttons {
+                if let Some(load_board) = &mut button.load_board {
+                    if load_board.id.is_none() {
+                        if let Some(path) = &load_board.path {
+                            load_board.id = ids_by_path.get(&normalize_path(path)).cloned();
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut image_paths: BTreeMap<String, String> = manifest.paths.images.clone();
+        let mut sound_paths: BTreeMap<String, String> = manifest.paths.sounds.clone();
+        for board in &boards {
+            for image in &board.images {
+                if let Some(path) = &image.path {
+                    image_paths
+                        .entry(image.id.clone())
+                        .or_insert_with(|| path.clone());
+                }
+            }
+            for sound in &board.sounds {
+                if let Some(path) = &sound.path {
+                    sound_paths
+                        .entry(sound.id.clone())
+                        .or_insert_with(|| path.clone());
+                }
+            }
+        }
+
+        let images = read_assets(&mut archive, image_paths)?;
+        let sounds = read_assets(&mut archive, sound_paths)?;
+
+        Ok(Self {
+            root,
+            boards,
+            images,
+            sounds,
+        })
+    }
+
+    /// Write the package as an OBZ archive, returning the underlying writer.
+    ///
+    /// Boards are stored under `boards/`, images under `images/` and sounds
+    /// under `sounds/`. Links to boards in the package get a `load_board.path`,
+    /// and images and sounds with attached data get a `path`; those without
+    /// lose theirs. Data for assets no board uses is written too, and listed
+    /// in the manifest. File names are derived from IDs, with a numeric suffix
+    /// where two IDs map to the same name.
+    ///
+    /// Fails if two boards share an ID.
+    pub fn write<W: Write + Seek>(&self, writer: W) -> Result<W, ObfError> {
+        if self.root_board().is_none() {
+            return Err(ObfError::MissingEntry(self.root.clone()));
+        }
+        let mut ids = HashSet::new();
+        if let Some(board) = self.boards.iter().find(|b| !ids.insert(b.id.as_str())) {
+            return Err(ObfError::InvalidData(format!(
+                "Duplicate board ID: {}",
+                board.id
+            )));
+        }
+
+        // IDs that differ only in unsafe characters share a file stem
+        let mut used = HashSet::new();
+        let mut paths = ObzPaths::default();
+        for board in &self.boards {
+            let path = unique_path(&mut used, format!("boards/{}.obf", file_stem(&board.id)));
+            paths.boards.insert(board.id.clone(), path);
+        }
+
+        let mut zip = ZipWriter::new(writer);
+        let options = SimpleFileOptions::default();
+
+        for board in &self.boards {
+            let mut board = board.clone();
+            for button in &mut board.buttons {
+                if let Some(load_board) = &mut button.load_board {
+                    if let Some(path) = load_board.id.as_ref().and_then(|id| paths.boards.get(id)) {
+                        load_board.path = Some(path.clone());
+                    }
+                }
+            }
+            for image in &mut board.images {
+                if self.images.contains_key(&image.id) {
+                    let path = paths.images.entry(image.id.clone()).or_insert_with(|| {
+                        let path = asset_path(
+                            "images",
+                            &image.id,
+                            image.path.as_deref(),
+                            image.content_type.as_deref(),
+                        );
+                        unique_path(&mut used, path)
+                    });
+                    image.path = Some(path.clone());
+                } else {
+                    // Nothing will be written there
+                    image.path = None;
+                }
+            }
+            for sound in &mut board.sounds {
+                if self.sounds.contains_key(&sound.id) {
+                    let path = paths.sounds.entry(sound.id.clone()).or_insert_with(|| {
+                        let path = asset_path(
+                            "sounds",
+                            &sound.id,
+                            sound.path.as_deref(),
+                            sound.content_type.as_deref(),
+                        );
+                        unique_path(&mut used, path)
+                    });
+                    sound.path = Some(path.clone());
+                } else {
+                    sound.path = None;
+                }
+            }
+
+            zip.start_file(paths.boards[&board.id].as_str(), options)?;
+            zip.write_all(&serde_json::to_vec_pretty(&board)?)?;
+        }
+
+        add_unused_assets(&mut paths.images, &self.images, "images", &mut used);
+        add_unused_assets(&mut paths.sounds, &self.sounds, "sounds", &mut used);
+
+        for (id, path) in &paths.images {
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(&self.images[id])?;
+        }
+        for (id, path) in &paths.sounds {
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(&self.sounds[id])?;
+        }
+
+        let manifest = ObzManifest {
+            format: OBF_FORMAT.to_string(),
+            root: paths.boards[&self.root].clone(),
+            paths,
+        };
+        zip.start_file(MANIFEST_PATH, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+        Ok(zip.finish()?)
+    }
+}
+
+/// Read an entry from the archive, mapping a missing file to [`ObfError::MissingEntry`].
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+) -> Result<Vec<u8>, ObfError> {
+    let mut file = match archive.by_name(path) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Err(ObfError::MissingEntry(path.to_string())),
+        Err(err) => return Err(err.into()),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Give paths to assets no board uses, in a stable order.
+fn add_unused_assets(
+    paths: &mut BTreeMap<String, String>,
+    assets: &HashMap<String, Vec<u8>>,
+    dir: &str,
+    used: &mut HashSet<String>,
+) {
+    let mut unused: Vec<&String> = assets
+        .keys()
+        .filter(|id| !paths.contains_key(*id))
+        .collect();
+    unused.sort();
+    for id in unused {
+        let path = unique_path(used, asset_path(dir, id, None, None));
+        paths.insert(id.clone(), path);
+    }
+}
+
+/// Read the assets in a path table, skipping any missing from the archive.
+fn read_assets<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    paths: BTreeMap<String, String>,
+) -> Result<HashMap<String, Vec<u8>>, ObfError> {
+    let mut assets = HashMap::new();
+    for (id, path) in paths {
+        match read_entry(archive, &normalize_path(&path)) {
+            Ok(data) => {
+                assets.insert(id, data);
+            }
+            Err(ObfError::MissingEntry(path)) => {
+                log::warn!("Skipping asset {}: {} is not in the package", id, path);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(assets)
+}
+
+/// Strip leading `./` and `/` so manifest and board paths match archive entry names.
+fn normalize_path(path: &str) -> String {
+    let mut path = path.trim();
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix('/') {
+            path = rest;
+        } else {
+            return path.to_string();
+        }
+    }
+}
+
+/// Turn a resource ID into a safe file name.
+fn file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Add a numeric suffix to `path` until it isn't in `used`, then claim it.
+fn unique_path(used: &mut HashSet<String>, path: String) -> String {
+    let (base, ext) = match path.rsplit_once('.') {
+        Some((base, ext)) if !ext.contains('/') => (base, format!(".{}", ext)),
+        _ => (path.as_str(), String::new()),
+    };
+    let mut candidate = path.clone();
+    let mut suffix = 2;
+    while used.contains(&candidate) {
+        candidate = format!("{}_{}{}", base, suffix, ext);
+        suffix += 1;
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+/// Build the package path for an asset, keeping its original extension if known.
+fn asset_path(dir: &str, id: &str, old_path: Option<&str>, content_type: Option<&str>) -> String {
+    let ext = old_path
+        .and_then(|p| p.rsplit('/').next())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_string())
+        .or_else(|| content_type.and_then(extension_for).map(str::to_string));
+
+    match ext {
+        Some(ext) => format!("{}/{}.{}", dir, file_stem(id), ext),
+        None => format!("{}/{}", dir, file_stem(id)),
+    }
+}
+
+/// Map a MIME type to a file extension.
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/svg+xml" => Some("svg"),
+        "image/webp" => Some("webp"),
+        "audio/mpeg" => Some("mp3"),
+        "audio/wav" | "audio/x-wav" => Some("wav"),
+        "audio/ogg" => Some("ogg"),
+        "audio/mp4" => Some("m4a"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obf::{ObfButton, ObfImage, ObfLoadBoard, ObfSound};
+
+    fn image(id: &str, content_type: &str) -> ObfImage {
+        ObfImage {
+            id: id.to_string(),
+            content_type: Some(content_type.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn sample_package() -> ObzPackage {
+        let mut home = ObfBoard::new("home", 1, 2);
+        let mut hello = ObfButton::speak("hello", "Hello");
+        hello.image_id = Some("img_heart".to_string());
+        hello.sound_id = Some("snd_hi".to_string());
+        home.add_button(hello);
+        home.add_button(ObfButton::navigate("more", "More", "feelings"));
+        home.place_button_at("hello", 0, 0);
+        home.place_button_at("more", 0, 1);
+        home.images.push(image("img_heart", "image/png"));
+        home.sounds.push(ObfSound {
+            id: "snd_hi".to_string(),
+            content_type: Some("audio/mpeg".to_string()),
+            ..Default::default()
+        });
+
+        let mut feelings = ObfBoard::new("feelings", 1, 1);
+        feelings.add_button(ObfButton::back("back"));
+        feelings.place_button_at("back", 0, 0);
+
+        ObzPackage::new(home)
+            .with_board(feelings)
+            .with_image("img_heart", vec![0x89, b'P', b'N', b'G'])
+            .with_sound("snd_hi", vec![1, 2, 3])
+    }
+
+    /// Build an archive by hand from (path, contents) pairs.
+    fn build_archive(entries: &[(&str, String)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in entries {
+            zip.start_file(*path, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_obz_roundtrip() {
+        let package = sample_package();
+        let bytes = package.to_bytes().unwrap();
+        let loaded = ObzPackage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.root, "home");
+        assert_eq!(loaded.boards.len(), 2);
+        assert_eq!(
+            loaded.image_data("img_heart"),
+            Some(&[0x89, b'P', b'N', b'G'][..])
+        );
+        assert_eq!(loaded.sound_data("snd_hi"), Some(&[1, 2, 3][..]));
+
+        let home = loaded.root_board().unwrap();
+        assert_eq!(home.images[0].path.as_deref(), Some("images/img_heart.png"));
+        assert_eq!(home.sounds[0].path.as_deref(), Some("sounds/snd_hi.mp3"));
+        let link = home.button("more").unwrap().load_board.as_ref().unwrap();
+        assert_eq!(link.path.as_deref(), Some("boards/feelings.obf"));
+    }
+
+    #[test]
+    fn test_obz_colliding_ids() {
+        let mut home = ObfBoard::new("a.b", 1, 1);
+        home.add_button(ObfButton::navigate("next", "Next", "a_b"));
+        home.place_button_at("next", 0, 0);
+        let mut other = ObfBoard::new("a_b", 1, 1);
+        for board in [&mut home, &mut other] {
+            for id in ["a.b", "a_b"] {
+                board.images.push(image(id, "image/png"));
+                board.sounds.push(ObfSound {
+                    id: id.to_string(),
+                    content_type: Some("audio/mpeg".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        let package = ObzPackage::new(home)
+            .with_board(other)
+            .with_image("a.b", vec![1])
+            .with_image("a_b", vec![2])
+            .with_sound("a.b", vec![3])
+            .with_sound("a_b", vec![4]);
+
+        let loaded = ObzPackage::from_bytes(&package.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.board("a.b").unwrap().id, "a.b");
+        assert_eq!(loaded.board("a_b").unwrap().id, "a_b");
+        assert_eq!(loaded.image_data("a.b"), Some(&[1][..]));
+        assert_eq!(loaded.image_data("a_b"), Some(&[2][..]));
+        assert_eq!(loaded.sound_data("a.b"), Some(&[3][..]));
+        assert_eq!(loaded.sound_data("a_b"), Some(&[4][..]));
+
+        let home = loaded.root_board().unwrap();
+        let link = home.button("next").unwrap().load_board.as_ref().unwrap();
+        assert_eq!(link.path.as_deref(), Some("boards/a_b_2.obf"));
+        assert_eq!(home.images[1].path.as_deref(), Some("images/a_b_2.png"));
+        // Shared assets keep one path across boards
+        assert_eq!(loaded.board("a_b").unwrap().images, home.images);
+    }
+
+    #[test]
+    fn test_obz_assets_without_data_or_boards() {
+        let mut home = ObfBoard::new("home", 1, 1);
+        home.images.push(ObfImage {
+            path: Some("images/remote.png".to_string()),
+            ..image("img_remote", "image/png")
+        });
+        let package = ObzPackage::new(home)
+            .with_image("img_unused", vec![1])
+            .with_sound("snd_unused", vec![2]);
+
+        let loaded = ObzPackage::from_bytes(&package.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.root_board().unwrap().images[0].path, None);
+        assert_eq!(loaded.image_data("img_unused"), Some(&[1][..]));
+        assert_eq!(loaded.sound_data("snd_unused"), Some(&[2][..]));
+    }
+
+    #[test]
+    fn test_obz_duplicate_board_ids() {
+        let package =
+            ObzPackage::new(ObfBoard::new("home", 1, 1)).with_board(ObfBoard::new("home", 2, 2));
+        assert!(matches!(package.to_bytes(), Err(ObfError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_obz_missing_asset_skipped() {
+        let mut home = ObfBoard::new("home", 1, 1);
+        home.images.push(ObfImage {
+            path: Some("images/gone.png".to_string()),
+            ..image("img_gone", "image/png")
+        });
+        let bytes = build_archive(&[
+            (
+                MANIFEST_PATH,
+                r#"{"format": "open-board-0.1", "root": "boards/home.obf"}"#.to_string(),
+            ),
+            ("boards/home.obf", serde_json::to_string(&home).unwrap()),
+        ]);
+
+        let loaded = ObzPackage::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.root_board().unwrap().id, "home");
+        assert_eq!(loaded.image_data("img_gone"), None);
+    }
+
+    #[test]
+    fn test_obz_manifest_written() {
+        let bytes = sample_package().to_bytes().unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let manifest: ObzManifest =
+            serde_json::from_slice(&read_entry(&mut archive, MANIFEST_PATH).unwrap()).unwrap();
+
+        assert_eq!(manifest.format, OBF_FORMAT);
+        assert_eq!(manifest.root, "boards/home.obf");
+        assert_eq!(manifest.paths.boards.len(), 2);
+        assert_eq!(
+            manifest.paths.images.get("img_heart").map(String::as_str),
+            Some("images/img_heart.png")
+        );
+    }
+
+    #[test]
+    fn test_obz_resolves_path_links() {
+        let mut root = ObfBoard::new("1", 1, 1);
+        let mut link = ObfButton::new("go", "Go");
+        link.load_board = Some(ObfLoadBoard {
+            path: Some("./boards/second.obf".to_string()),
+            ..Default::default()
+        });
+        root.add_button(link);
+        root.place_button_at("go", 0, 0);
+        let second = ObfBoard::new("second-board", 1, 1);
+
+        // The manifest only lists the root; the second board is found via the link.
+        let bytes = build_archive(&[
+            (
+                MANIFEST_PATH,
+                r#"{"format": "open-board-0.1", "root": "boards/root.obf"}"#.to_string(),
+            ),
+            ("boards/root.obf", serde_json::to_string(&root).unwrap()),
+            ("boards/second.obf", serde_json::to_string(&second).unwrap()),
+        ]);
+
+        let package = ObzPackage::from_bytes(&bytes).unwrap();
+        assert_eq!(package.boards.len(), 2);
+        let link = package.root_board().unwrap().button("go").unwrap();
+        assert_eq!(
+            link.load_board.as_ref().unwrap().id.as_deref(),
+            Some("second-board")
+        );
+    }
+
+    #[test]
+    fn test_obz_missing_entry() {
+        let bytes = build_archive(&[(
+            MANIFEST_PATH,
+            r#"{"format": "open-board-0.1", "root": "boards/missing.obf"}"#.to_string(),
+        )]);
+
+        let result = ObzPackage::from_bytes(&bytes);
+        assert!(matches!(result, Err(ObfError::MissingEntry(p)) if p == "boards/missing.obf"));
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("./images/a.png"), "images/a.png");
+        assert_eq!(normalize_path("/boards/1.obf"), "boards/1.obf");
+        assert_eq!(normalize_path("boards/1.obf"), "boards/1.obf");
+    }
+}
@@ -0,0 +1,424 @@
+//! Structural validation for OBF boards.
+//!
+//! A board can deserialize cleanly and still be broken: the grid may point at
+//! buttons that don't exist, or a button may reference an image that was never
+//! declared. [`validate`] finds these problems and reports each one as a
+//! [`Diagnostic`] with a stable code and a JSON-pointer location, so board
+//! authoring tools can show exactly what is wrong and where.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lovewords_core::obf::{validate, DiagnosticCode};
+//! use lovewords_core::ObfBoard;
+//!
+//! let mut board = ObfBoard::new("broken", 1, 1);
+//! board.place_button_at("missing", 0, 0);
+//!
+//! let diagnostics = validate(&board);
+//! assert_eq!(diagnostics[0].code, DiagnosticCode::UnknownButton);
+//! assert_eq!(diagnostics[0].location, "/grid/order/0/0");
+//! ```
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use super::board::{ObfBoard, OBF_FORMAT};
+use crate::error::ObfError;
+
+/// How serious a validation problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The board is usable but probably not what the author intended.
+    Warning,
+    /// The board will misbehave when displayed or activated.
+    Error,
+}
+
+/// Stable identifiers for each kind of validation problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCode {
+    /// The `format` string is not a supported OBF version.
+    UnsupportedFormat,
+    /// The board has an empty `id`.
+    MissingBoardId,
+    /// A button has an empty `id`.
+    MissingButtonId,
+    /// Two buttons share the same `id`.
+    DuplicateButtonId,
+    /// Two images share the same `id`.
+    DuplicateImageId,
+    /// Two sounds share the same `id`.
+    DuplicateSoundId,
+    /// `grid.order` has a different number of rows than `grid.rows`.
+    RowCountMismatch,
+    /// A row in `grid.order` has a different length than `grid.columns`.
+    ColumnCountMismatch,
+    /// `grid.order` references a button ID that is not in `buttons`.
+    UnknownButton,
+    /// A button's `image_id` is not in `images`.
+    UnknownImage,
+    /// A button's `sound_id` is not in `sounds`.
+    UnknownSound,
+    /// A button is defined but never placed in the grid.
+    UnplacedButton,
+}
+
+impl DiagnosticCode {
+    /// Get the stable string form of this code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::UnsupportedFormat => "unsupported_format",
+            DiagnosticCode::MissingBoardId => "missing_board_id",
+            DiagnosticCode::MissingButtonId => "missing_button_id",
+            DiagnosticCode::DuplicateButtonId => "duplicate_button_id",
+            DiagnosticCode::DuplicateImageId => "duplicate_image_id",
+            DiagnosticCode::DuplicateSoundId => "duplicate_sound_id",
+            DiagnosticCode::RowCountMismatch => "row_count_mismatch",
+            DiagnosticCode::ColumnCountMismatch => "column_count_mismatch",
+            DiagnosticCode::UnknownButton => "unknown_button",
+            DiagnosticCode::UnknownImage => "unknown_image",
+            DiagnosticCode::UnknownSound => "unknown_sound",
+            DiagnosticCode::UnplacedButton => "unplaced_button",
+        }
+    }
+
+    /// Get the default severity for this code.
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticCode::UnplacedButton => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single problem found while validating a board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+
+    /// Stable code identifying the kind of problem.
+    pub code: DiagnosticCode,
+
+    /// JSON pointer to the offending value (e.g. "/grid/order/1/2").
+    pub location: String,
+
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with the code's default severity.
+    pub fn new(
+        code: DiagnosticCode,
+        location: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: code.severity(),
+            code,
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Check if this diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Convert into the equivalent [`ObfError`].
+    pub fn to_error(&self) -> ObfError {
+        let detail = format!("{} at {}", self.message, self.location);
+        match self.code {
+            DiagnosticCode::UnsupportedFormat => ObfError::UnsupportedFormat(detail),
+            DiagnosticCode::MissingBoardId | DiagnosticCode::MissingButtonId => {
+                ObfError::MissingField(detail)
+            }
+            _ => ObfError::InvalidData(detail),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.code, self.location, self.message)
+    }
+}
+
+impl From<Diagnostic> for ObfError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        diagnostic.to_error()
+    }
+}
+
+/// Check a board for structural problems.
+///
+/// Returns an empty vector if the board is well-formed. Diagnostics are
+/// reported in document order.
+pub fn validate(board: &ObfBoard) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if board.format != OBF_FORMAT {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticCode::UnsupportedFormat,
+            "/format",
+            format!("Expected format '{}', found '{}'", OBF_FORMAT, board.format),
+        ));
+    }
+
+    if board.id.is_empty() {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticCode::MissingBoardId,
+            "/id",
+            "Board ID is empty",
+        ));
+    }
+
+    let image_ids = collect_ids(
+        board.images.iter().map(|i| i.id.as_str()),
+        "images",
+        DiagnosticCode::DuplicateImageId,
+        "image",
+        &mut diagnostics,
+    );
+    let sound_ids = collect_ids(
+        board.sounds.iter().map(|s| s.id.as_str()),
+        "sounds",
+        DiagnosticCode::DuplicateSoundId,
+        "sound",
+        &mut diagnostics,
+    );
+
+    let mut button_ids = HashSet::new();
+    for (i, button) in board.buttons.iter().enumerate() {
+        if button.id.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticCode::MissingButtonId,
+                format!("/buttons/{}/id", i),
+                "Button ID is empty",
+            ));
+        } else if !button_ids.insert(button.id.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticCode::DuplicateButtonId,
+                format!("/buttons/{}/id", i),
+                format!("Duplicate button ID '{}'", button.id),
+            ));
+        }
+
+        if let Some(image_id) = &button.image_id {
+            if !image_ids.contains(image_id.as_str()) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::UnknownImage,
+                    format!("/buttons/{}/image_id", i),
+                    format!("Image '{}' is not defined in images", image_id),
+                ));
+            }
+        }
+
+        if let Some(sound_id) = &button.sound_id {
+            if !sound_ids.contains(sound_id.as_str()) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::UnknownSound,
+                    format!("/buttons/{}/sound_id", i),
+                    format!("Sound '{}' is not defined in sounds", sound_id),
+                ));
+            }
+        }
+    }
+
+    let grid = &board.grid;
+    if grid.order.len() != grid.rows {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticCode::RowCountMismatch,
+            "/grid/order",
+            format!(
+                "Grid declares {} rows but order has {}",
+                grid.rows,
+                grid.order.len()
+            ),
+        ));
+    }
+
+    let mut placed = HashSet::new();
+    for (row, cells) in grid.order.iter().enumerate() {
+        if cells.len() != grid.columns {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticCode::ColumnCountMismatch,
+                format!("/grid/order/{}", row),
+                format!(
+                    "Grid declares {} columns but row {} has {}",
+                    grid.columns,
+                    row,
+                    cells.len()
+                ),
+            ));
+        }
+
+        for (col, id) in cells.iter().enumerate() {
+            let Some(id) = id else { continue };
+            if button_ids.contains(id.as_str()) {
+                placed.insert(id.as_str());
+            } else {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::UnknownButton,
+                    format!("/grid/order/{}/{}", row, col),
+                    format!("Button '{}' is not defined in buttons", id),
+                ));
+            }
+        }
+    }
+
+    for (i, button) in board.buttons.iter().enumerate() {
+        if !button.id.is_empty() && !placed.contains(button.id.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticCode::UnplacedButton,
+                format!("/buttons/{}", i),
+                format!("Button '{}' is not placed in the grid", button.id),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Collect resource IDs, reporting duplicates.
+fn collect_ids<'a>(
+    ids: impl Iterator<Item = &'a str>,
+    field: &str,
+    code: DiagnosticCode,
+    kind: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashSet<&'a str> {
+    let mut seen = HashSet::new();
+    for (i, id) in ids.enumerate() {
+        if !seen.insert(id) {
+            diagnostics.push(Diagnostic::new(
+                code,
+                format!("/{}/{}/id", field, i),
+                format!("Duplicate {} ID '{}'", kind, id),
+            ));
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obf::ObfButton;
+
+    fn codes(board: &ObfBoard) -> Vec<DiagnosticCode> {
+        validate(board).into_iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_valid_board() {
+        let mut board = ObfBoard::new("ok", 1, 2);
+        board.add_button(ObfButton::speak("btn_1", "Hello"));
+        board.place_button_at("btn_1", 0, 0);
+
+        assert!(validate(&board).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_button_in_grid() {
+        let mut board = ObfBoard::new("test", 2, 2);
+        board.place_button_at("ghost", 1, 0);
+
+        let diagnostics = validate(&board);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::UnknownButton);
+        assert_eq!(diagnostics[0].location, "/grid/order/1/0");
+        assert!(diagnostics[0].is_error());
+    }
+
+    #[test]
+    fn test_duplicate_button_ids() {
+        let mut board = ObfBoard::new("test", 1, 2);
+        board.add_button(ObfButton::speak("btn", "One"));
+        board.add_button(ObfButton::speak("btn", "Two"));
+        board.place_button_at("btn", 0, 0);
+
+        let diagnostics = validate(&board);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::DuplicateButtonId);
+        assert_eq!(diagnostics[0].location, "/buttons/1/id");
+    }
+
+    #[test]
+    fn test_grid_shape_mismatch() {
+        let mut board = ObfBoard::new("test", 2, 3);
+        board.grid.order.pop();
+        board.grid.order[0].push(None);
+
+        assert_eq!(
+            codes(&board),
+            vec![
+                DiagnosticCode::RowCountMismatch,
+                DiagnosticCode::ColumnCountMismatch
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dangling_image_and_sound() {
+        let mut board = ObfBoard::new("test", 1, 1);
+        let mut button = ObfButton::speak("btn", "Hi");
+        button.image_id = Some("img".to_string());
+        button.sound_id = Some("snd".to_string());
+        board.add_button(button);
+        board.place_button_at("btn", 0, 0);
+
+        let diagnostics = validate(&board);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::UnknownImage);
+        assert_eq!(diagnostics[0].location, "/buttons/0/image_id");
+        assert_eq!(diagnostics[1].code, DiagnosticCode::UnknownSound);
+        assert_eq!(diagnostics[1].location, "/buttons/0/sound_id");
+    }
+
+    #[test]
+    fn test_wrong_format() {
+        let mut board = ObfBoard::new("test", 1, 1);
+        board.format = "open-board-9".to_string();
+
+        let diagnostics = validate(&board);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::UnsupportedFormat);
+        assert!(matches!(
+            diagnostics[0].to_error(),
+            ObfError::UnsupportedFormat(_)
+        ));
+    }
+
+    #[test]
+    fn test_unplaced_button_is_warning() {
+        let mut board = ObfBoard::new("test", 1, 1);
+        board.add_button(ObfButton::speak("spare", "Spare"));
+
+        let diagnostics = validate(&board);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::UnplacedButton);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_diagnostic_json() {
+        let diagnostic = Diagnostic::new(
+            DiagnosticCode::UnknownButton,
+            "/grid/order/0/0",
+            "Button 'x' is not defined in buttons",
+        );
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["code"], "unknown_button");
+        assert_eq!(json["location"], "/grid/order/0/0");
+    }
+}
@@ -0,0 +1,64 @@
+//! Events emitted by a [`Session`](super::Session) for the UI.
+
+use std::time::Duration;
+
+use crate::input::{Focus, ScanState};
+
+/// Something the UI should reflect after a session handled input.
+///
+/// Events are returned in the order the effects happened, so a button that
+/// speaks and then navigates yields [`Spoke`](SessionEvent::Spoke) before
+/// [`BoardChanged`](SessionEvent::BoardChanged).
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// A cell was activated.
+    CellActivated {
+        row: usize,
+        col: usize,
+        button_id: String,
+    },
+
+    /// Text was sent to the speech engine.
+    Spoke(String),
+
+    /// The current board changed.
+    BoardChanged { board_id: String },
+
+    /// The message bar contents changed.
+    MessageChanged(String),
+
+    /// A recorded sound should be played (clients own audio playback).
+    PlaySound(String),
+
+    /// A vendor extension action (`:ext_<namespace>_<name>`) was requested.
+    Extension { namespace: String, name: String },
+
+    /// A platform-specific action was requested.
+    Custom(String),
+
+    /// The keyboard cursor moved.
+    CursorMoved(Option<(usize, usize)>),
+
+    /// Keyboard focus moved to the message bar or toolbar.
+    ///
+    /// Focus on a cell is reported as [`CursorMoved`](SessionEvent::CursorMoved).
+    FocusChanged(Focus),
+
+    /// The scanner state changed.
+    ScanChanged(ScanState),
+
+    /// A scan highlight was announced in the prompt voice.
+    Announced(String),
+
+    /// The dots and dashes of the Morse letter being entered changed.
+    MorseChanged(String),
+
+    /// The scan rate tuner suggests a new scan interval.
+    ScanIntervalSuggested(Duration),
+
+    /// The scan interval was changed to fit the user's reaction times.
+    ScanIntervalChanged(Duration),
+
+    /// A voice command was not understood, or named no cell or board.
+    VoiceUnrecognized(String),
+}
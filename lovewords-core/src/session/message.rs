@@ -0,0 +1,384 @@
+//! Message bar (sentence builder) model.
+//!
+//! The message bar collects the words a user selects so they can compose a
+//! sentence like "I love you so much mom" and speak it in one go. Each word
+//! is a [`MessageToken`] that remembers which button produced it, so clients
+//! can show the button's image next to the word.
+//!
+//! Spelling keyboards append letters with [`MessageBar::append_text`]; the
+//! letters merge into a single partial word until [`MessageBar::space`]
+//! finishes it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SpeechError;
+use crate::speech::{SpeechEngine, VoiceConfig};
+
+/// A single word or phrase in the message bar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageToken {
+    /// Text of the token.
+    pub text: String,
+
+    /// ID of the button that produced this token, if any.
+    pub button_id: Option<String>,
+
+    /// ID of the image shown for this token, if any.
+    pub image_id: Option<String>,
+
+    /// Whether this is a word still being spelled letter by letter.
+    #[serde(default)]
+    pub partial: bool,
+}
+
+impl MessageToken {
+    /// Create a token from plain text.
+    pub fn word(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            button_id: None,
+            image_id: None,
+            partial: false,
+        }
+    }
+
+    /// Create a token produced by a button.
+    pub fn from_button(
+        text: impl Into<String>,
+        button_id: impl Into<String>,
+        image_id: Option<String>,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            button_id: Some(button_id.into()),
+            image_id,
+            partial: false,
+        }
+    }
+}
+
+/// The message bar: an editable sequence of tokens with a cursor.
+///
+/// The cursor is a token index; new tokens are inserted at the cursor and
+/// backspace removes the token before it. Appending always moves the cursor
+/// to the end.
+///
+/// # Example
+///
+/// ```rust
+/// use lovewords_core::session::{MessageBar, MessageToken};
+///
+/// let mut bar = MessageBar::new();
+/// bar.append(MessageToken::word("I"));
+/// bar.append(MessageToken::word("love"));
+/// bar.append(MessageToken::word("you"));
+/// assert_eq!(bar.text(), "I love you");
+///
+/// bar.backspace_word();
+/// assert_eq!(bar.text(), "I love");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageBar {
+    /// Tokens in order.
+    tokens: Vec<MessageToken>,
+
+    /// Insertion point, in `0..=tokens.len()`.
+    cursor: usize,
+}
+
+impl MessageBar {
+    /// Create an empty message bar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the tokens in order.
+    pub fn tokens(&self) -> &[MessageToken] {
+        &self.tokens
+    }
+
+    /// Get the number of tokens.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Check if the message bar is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Get the full message as text, with tokens separated by spaces.
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Get the cursor position (a token index).
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Move the cursor, clamping to the valid range.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.tokens.len());
+    }
+
+    /// Move the cursor one token to the left.
+    pub fn cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Move the cursor one token to the right.
+    pub fn cursor_right(&mut self) {
+        self.set_cursor(self.cursor + 1);
+    }
+
+    /// Add a token at the end and move the cursor to the end.
+    ///
+    /// Any word being spelled is finished first.
+    pub fn append(&mut self, token: MessageToken) {
+        self.cursor = self.tokens.len();
+        self.space();
+        self.tokens.push(token);
+        self.cursor = self.tokens.len();
+    }
+
+    /// Insert a token at the cursor and move the cursor past it.
+    ///
+    /// Any word being spelled is finished first.
+    pub fn insert(&mut self, token: MessageToken) {
+        self.space();
+        self.tokens.insert(self.cursor, token);
+        self.cursor += 1;
+    }
+
+    /// Append letters to the word being spelled at the cursor.
+    ///
+    /// Starts a new partial word if the token before the cursor is complete.
+    pub fn append_text(&mut self, text: &str) {
+        match self.partial_token_mut() {
+            Some(token) => token.text.push_str(text),
+            None => {
+                self.tokens.insert(
+                    self.cursor,
+                    MessageToken {
+                        partial: true,
+                        ..MessageToken::word(text)
+                    },
+                );
+                self.cursor += 1;
+            }
+        }
+    }
+
+    /// Finish the word being spelled, if any.
+    ///
+    /// Returns `true` if a partial word was finished.
+    pub fn space(&mut self) -> bool {
+        match self.partial_token_mut() {
+            Some(token) => {
+                token.partial = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check if a word is being spelled at the cursor.
+    pub fn is_spelling(&self) -> bool {
+        self.cursor
+            .checked_sub(1)
+            .is_some_and(|i| self.tokens[i].partial)
+    }
+
+    /// Remove a letter while spelling, otherwise the previous word.
+    ///
+    /// Returns `true` if anything was removed.
+    pub fn backspace(&mut self) -> bool {
+        if self.is_spelling() {
+            self.backspace_char().is_some()
+        } else {
+            self.backspace_word().is_some()
+        }
+    }
+
+    /// Remove the token before the cursor.
+    ///
+    /// Returns the removed token, or `None` if the cursor is at the start.
+    pub fn backspace_word(&mut self) -> Option<MessageToken> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        Some(self.tokens.remove(self.cursor))
+    }
+
+    /// Remove the last character of the token before the cursor.
+    ///
+    /// The token is removed entirely once it has no characters left.
+    /// Returns the removed character, if any.
+    pub fn backspace_char(&mut self) -> Option<char> {
+        let index = self.cursor.checked_sub(1)?;
+        let token = &mut self.tokens[index];
+        let removed = token.text.pop();
+        if token.text.is_empty() {
+            self.tokens.remove(index);
+            self.cursor -= 1;
+        }
+        removed
+    }
+
+    fn partial_token_mut(&mut self) -> Option<&mut MessageToken> {
+        let index = self.cursor.checked_sub(1)?;
+        self.tokens.get_mut(index).filter(|t| t.partial)
+    }
+
+    /// Remove all tokens.
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+        self.cursor = 0;
+    }
+
+    /// Speak the whole message.
+    ///
+    /// Returns the text spoken, or `None` if the message bar is empty.
+    pub fn speak_all(
+        &self,
+        engine: &dyn SpeechEngine,
+        config: &VoiceConfig,
+    ) -> Result<Option<String>, SpeechError> {
+        let text = self.text();
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        engine.speak(&text, config)?;
+        Ok(Some(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::speech::MemorySpeech;
+
+    fn bar(words: &[&str]) -> MessageBar {
+        let mut bar = MessageBar::new();
+        for word in words {
+            bar.append(MessageToken::word(*word));
+        }
+        bar
+    }
+
+    #[test]
+    fn test_append_and_text() {
+        let bar = bar(&["I", "love", "you", "so", "much", "mom"]);
+        assert_eq!(bar.text(), "I love you so much mom");
+        assert_eq!(bar.cursor(), 6);
+    }
+
+    #[test]
+    fn test_insert_at_cursor() {
+        let mut bar = bar(&["I", "you"]);
+        bar.set_cursor(1);
+        bar.insert(MessageToken::word("love"));
+
+        assert_eq!(bar.text(), "I love you");
+        assert_eq!(bar.cursor(), 2);
+
+        // Backspace works relative to the cursor
+        bar.backspace_word();
+        assert_eq!(bar.text(), "I you");
+    }
+
+    #[test]
+    fn test_backspace_char() {
+        let mut bar = bar(&["hi", "mo"]);
+        assert_eq!(bar.backspace_char(), Some('o'));
+        assert_eq!(bar.text(), "hi m");
+
+        // Removing the last character removes the token
+        bar.backspace_char();
+        assert_eq!(bar.text(), "hi");
+        assert_eq!(bar.len(), 1);
+    }
+
+    #[test]
+    fn test_backspace_at_start() {
+        let mut bar = bar(&["hi"]);
+        bar.set_cursor(0);
+        assert!(bar.backspace_word().is_none());
+        assert!(bar.backspace_char().is_none());
+
+        bar.clear();
+        assert!(bar.is_empty());
+        assert_eq!(bar.cursor(), 0);
+    }
+
+    #[test]
+    fn test_token_origin() {
+        let mut bar = MessageBar::new();
+        bar.append(MessageToken::from_button(
+            "mom",
+            "btn_mom",
+            Some("img_mom".to_string()),
+        ));
+
+        let token = &bar.tokens()[0];
+        assert_eq!(token.button_id.as_deref(), Some("btn_mom"));
+        assert_eq!(token.image_id.as_deref(), Some("img_mom"));
+    }
+
+    #[test]
+    fn test_spelling_merges_letters() {
+        let mut bar = bar(&["I", "love"]);
+        bar.append_text("m");
+        bar.append_text("o");
+        bar.append_text("m");
+        assert_eq!(bar.text(), "I love mom");
+        assert_eq!(bar.len(), 3);
+        assert!(bar.is_spelling());
+
+        // Backspace removes letters while spelling
+        bar.backspace();
+        assert_eq!(bar.text(), "I love mo");
+
+        // Space finishes the word; the next letter starts a new one
+        assert!(bar.space());
+        assert!(!bar.is_spelling());
+        bar.append_text("x");
+        assert_eq!(bar.text(), "I love mo x");
+
+        // Backspace after a finished word removes the whole word
+        bar.space();
+        bar.backspace();
+        assert_eq!(bar.text(), "I love mo");
+    }
+
+    #[test]
+    fn test_append_finishes_spelled_word() {
+        let mut bar = MessageBar::new();
+        bar.append_text("h");
+        bar.append_text("i");
+        bar.append(MessageToken::word("mom"));
+
+        assert_eq!(bar.text(), "hi mom");
+        assert!(!bar.tokens()[0].partial);
+    }
+
+    #[test]
+    fn test_speak_all() {
+        let engine = MemorySpeech::new();
+        let config = VoiceConfig::default();
+
+        assert_eq!(MessageBar::new().speak_all(&engine, &config).unwrap(), None);
+
+        let spoken = bar(&["I", "love", "you"])
+            .speak_all(&engine, &config)
+            .unwrap();
+        assert_eq!(spoken.as_deref(), Some("I love you"));
+        assert_eq!(engine.spoken(), vec!["I love you"]);
+    }
+}
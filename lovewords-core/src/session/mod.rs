@@ -0,0 +1,1620 @@
+//! Interactive session tying input, navigation and speech together.
+//!
+//! A [`Session`] is the piece every client needs: it owns the board
+//! navigator, the [`MessageBar`] and the scanner, turns [`InputEvent`]s into
+//! [`CellAction`]s, executes them against a [`SpeechEngine`] and a
+//! [`BoardResolver`], and reports what happened as [`SessionEvent`]s.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use lovewords_core::board::ResolverChain;
+//! use lovewords_core::session::{Session, SessionEvent};
+//! use lovewords_core::speech::MemorySpeech;
+//! use lovewords_core::{InputEvent, ObfBoard, ObfButton};
+//!
+//! let mut home = ObfBoard::new("home", 1, 1);
+//! home.add_button(ObfButton::speak("hi", "Hello"));
+//! home.place_button_at("hi", 0, 0);
+//!
+//! let speech = Arc::new(MemorySpeech::new());
+//! let mut session = Session::new(home, speech.clone(), Arc::new(ResolverChain::new()));
+//!
+//! let events = session.handle_input(InputEvent::tap(0, 0)).unwrap();
+//! assert!(events.contains(&SessionEvent::Spoke("Hello".to_string())));
+//! assert_eq!(speech.spoken(), vec!["Hello"]);
+//! ```
+
+mod event;
+mod message;
+mod recording;
+
+pub use event::SessionEvent;
+pub use message::{MessageBar, MessageToken};
+pub use recording::{InputRecord, RecordedInput, Recording, ReplayReport, ReplayStep, Replayer};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::board::{BoardNavigator, BoardResolver, Cell, CellAction};
+use crate::error::Result;
+use crate::input::{
+    BindingMap, Clock, Focus, InputEvent, InputRouter, KeyEvent, KeyGuard, KeyOutcome,
+    KeyboardNavigator, MorseCommand, MorseDecoder, MorseOutput, PhysicalInput, RouterConfig,
+    ScanDriver, ScanRateTuner, ScanState, Scanner, SwitchOutcome, SystemClock, VoiceCommand,
+    VoiceGrammar,
+};
+use crate::obf::{ObfBoard, ObfLoadBoard};
+use crate::speech::{ScanPrompter, SpeechEngine, VoiceConfig};
+use crate::storage::{
+    AccessibilitySettings, ProfileSettings, ScanRateMode, ScanRateSettings, SpeakMode,
+    StorageBackend, SwitchAction, UsageStats,
+};
+
+/// A running communication session.
+pub struct Session {
+    /// Board navigation state.
+    navigator: BoardNavigator,
+
+    /// Switch scanner, sized to the current board, and its timing.
+    scan: ScanDriver,
+
+    /// Sentence being composed.
+    message: MessageBar,
+
+    /// Whether selections are spoken or only added to the message bar.
+    speak_mode: SpeakMode,
+
+    /// Engine used for all speech output.
+    speech: Arc<dyn SpeechEngine>,
+
+    /// Resolver used to follow board links.
+    resolver: Arc<dyn BoardResolver>,
+
+    /// Voice used for speech output.
+    voice: VoiceConfig,
+
+    /// Prompt voice for auditory scanning, if enabled.
+    prompter: Option<ScanPrompter>,
+
+    /// Selection counts, for frequency-ordered scanning.
+    usage: UsageStats,
+
+    /// Filter for accidental touches and switch presses.
+    guard: KeyGuard,
+
+    /// Arbitration between input methods.
+    router: InputRouter,
+
+    /// Physical key and button bindings.
+    bindings: BindingMap,
+
+    /// Keyboard focus handling.
+    keys: KeyboardNavigator,
+
+    /// Morse decoder, if switches are used for Morse code instead of scanning.
+    morse: Option<MorseDecoder>,
+
+    /// Grammar for voice commands.
+    voice_grammar: VoiceGrammar,
+
+    /// Storage searched for boards opened by name.
+    storage: Option<Arc<dyn StorageBackend>>,
+
+    /// Inputs handled so far, while recording.
+    recording: Option<Recording>,
+
+    /// Scan interval learned from switch reaction times.
+    scan_rate: ScanRateTuner,
+}
+
+impl Session {
+    /// Create a session starting at the given home board.
+    pub fn new(
+        home: ObfBoard,
+        speech: Arc<dyn SpeechEngine>,
+        resolver: Arc<dyn BoardResolver>,
+    ) -> Self {
+        let mut scanner = Scanner::new(home.grid.rows, home.grid.columns);
+        scanner.set_board(&home);
+        let scan_rate =
+            ScanRateTuner::new(ScanRateSettings::default(), scanner.config().scan_interval);
+        let scan = ScanDriver::new(scanner, Arc::new(SystemClock::new()));
+        Self {
+            navigator: BoardNavigator::new(home),
+            scan,
+            message: MessageBar::new(),
+            speak_mode: SpeakMode::default(),
+            speech,
+            resolver,
+            voice: VoiceConfig::default(),
+            prompter: None,
+            usage: UsageStats::default(),
+            guard: KeyGuard::default(),
+            router: InputRouter::default(),
+            bindings: BindingMap::default(),
+            keys: KeyboardNavigator::default(),
+            morse: None,
+            voice_grammar: VoiceGrammar::default(),
+            storage: None,
+            recording: None,
+            scan_rate,
+        }
+    }
+
+    /// Apply voice, speak mode and scanning preferences from a profile.
+    pub fn with_settings(mut self, settings: &ProfileSettings) -> Self {
+        self.voice = settings.voice.to_voice_config();
+        self.speak_mode = settings.voice.speak_mode;
+        let scanner = self.scan.scanner_mut();
+        let mut config = scanner.config().clone();
+        config.scan_interval = settings.accessibility.scan_interval;
+        config.order = settings.accessibility.scan_order;
+        *scanner = scanner
+            .clone()
+            .with_mode(settings.accessibility.scan_mode)
+            .with_config(config);
+        self.scan.apply_input_settings(&settings.input);
+        self.scan_rate = ScanRateTuner::new(
+            settings.accessibility.scan_rate.clone(),
+            settings.accessibility.scan_interval,
+        );
+        self.guard = KeyGuard::new(settings.input.key_guard.clone());
+        self.router = InputRouter::new(RouterConfig::from_settings(settings));
+        self.bindings = settings.input.bindings.clone();
+        self.morse = settings
+            .input
+            .morse
+            .enabled
+            .then(|| MorseDecoder::new(settings.input.morse.clone()));
+        self.prompter = settings
+            .accessibility
+            .auditory_scanning
+            .then(|| ScanPrompter::new(settings.accessibility.prompt_voice.to_voice_config()));
+        self
+    }
+
+    /// Start from previously recorded usage, e.g. [`Profile::usage`].
+    ///
+    /// [`Profile::usage`]: crate::storage::Profile::usage
+    pub fn with_usage(mut self, usage: UsageStats) -> Self {
+        self.usage = usage;
+        self.fit_scanner();
+        self
+    }
+
+    /// Announce scan highlights in the given prompt voice (auditory scanning).
+    pub fn with_prompt_voice(mut self, voice: VoiceConfig) -> Self {
+        self.prompter = Some(ScanPrompter::new(voice));
+        self
+    }
+
+    /// Set the clock used for scan timing.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.scan.set_clock(clock);
+        self
+    }
+
+    /// Set the voice used for speech output.
+    pub fn with_voice(mut self, voice: VoiceConfig) -> Self {
+        self.voice = voice;
+        self
+    }
+
+    /// Set whether selections are spoken or only added to the message bar.
+    pub fn with_speak_mode(mut self, speak_mode: SpeakMode) -> Self {
+        self.speak_mode = speak_mode;
+        self
+    }
+
+    /// Set which input methods are enabled and how conflicts between them
+    /// are resolved.
+    pub fn with_router(mut self, config: RouterConfig) -> Self {
+        self.router = InputRouter::new(config);
+        self
+    }
+
+    /// Set how voice commands are parsed and matched.
+    pub fn with_voice_grammar(mut self, grammar: VoiceGrammar) -> Self {
+        self.voice_grammar = grammar;
+        self
+    }
+
+    /// Set the storage searched when a voice command opens a board by name.
+    ///
+    /// The session's resolver must be able to load boards from it, e.g. a
+    /// [`StorageResolver`](crate::board::StorageResolver) over the same
+    /// storage.
+    pub fn with_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Set how keyboard navigation moves between cells, the message bar
+    /// and the toolbar.
+    pub fn with_keyboard(mut self, keys: KeyboardNavigator) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Replace the scanner.
+    ///
+    /// The scanner is resized to the current board.
+    pub fn with_scanner(mut self, scanner: Scanner) -> Self {
+        self.scan_rate.set_interval(scanner.config().scan_interval);
+        *self.scan.scanner_mut() = scanner;
+        self.fit_scanner();
+        self
+    }
+
+    /// Get the board navigator.
+    pub fn navigator(&self) -> &BoardNavigator {
+        &self.navigator
+    }
+
+    /// Get the current board.
+    pub fn current_board(&self) -> &ObfBoard {
+        self.navigator.current()
+    }
+
+    /// Get the scanner.
+    pub fn scanner(&self) -> &Scanner {
+        self.scan.scanner()
+    }
+
+    /// Get mutable access to the scanner.
+    pub fn scanner_mut(&mut self) -> &mut Scanner {
+        self.scan.scanner_mut()
+    }
+
+    /// Get the scan driver.
+    pub fn scan_driver(&self) -> &ScanDriver {
+        &self.scan
+    }
+
+    /// Get the input router.
+    pub fn router(&self) -> &InputRouter {
+        &self.router
+    }
+
+    /// Get the scan rate tuner.
+    pub fn scan_rate(&self) -> &ScanRateTuner {
+        &self.scan_rate
+    }
+
+    /// Get the voice used for speech output.
+    pub fn voice(&self) -> &VoiceConfig {
+        &self.voice
+    }
+
+    /// Get the speak mode.
+    pub fn speak_mode(&self) -> SpeakMode {
+        self.speak_mode
+    }
+
+    /// Get the selection counts recorded so far.
+    ///
+    /// Save these back to the profile to keep learning across sessions.
+    pub fn usage(&self) -> &UsageStats {
+        &self.usage
+    }
+
+    /// Get the message bar.
+    pub fn message_bar(&self) -> &MessageBar {
+        &self.message
+    }
+
+    /// Get mutable access to the message bar.
+    ///
+    /// Edits made this way don't produce [`SessionEvent`]s.
+    pub fn message_bar_mut(&mut self) -> &mut MessageBar {
+        &mut self.message
+    }
+
+    /// Get the message bar text.
+    pub fn message(&self) -> String {
+        self.message.text()
+    }
+
+    /// Handle an input event and return the resulting events.
+    ///
+    /// The event first passes through the profile's key guard, which may
+    /// drop it or hold it back until [`tick`](Self::tick), then through the
+    /// input router, which drops it if its input method is disabled or it
+    /// conflicts with another method.
+    pub fn handle_input(&mut self, event: InputEvent) -> Result<Vec<SessionEvent>> {
+        self.record(RecordedInput::Event(event.clone()));
+        let accepted = self.guard.filter(event, self.scan.now());
+        self.dispatch_all(accepted)
+    }
+
+    /// Handle a physical key or button going down.
+    ///
+    /// The input is translated through the profile's bindings; unbound
+    /// inputs are ignored.
+    pub fn press_input(&mut self, input: &PhysicalInput) -> Result<Vec<SessionEvent>> {
+        match self.bindings.press(input) {
+            Some(event) => self.handle_input(event),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Handle a physical key or button going up.
+    pub fn release_input(&mut self, input: &PhysicalInput) -> Result<Vec<SessionEvent>> {
+        match self.bindings.release(input) {
+            Some(event) => self.handle_input(event),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Handle a finger or pointer touching the cell at a position.
+    ///
+    /// Reporting raw touches lets the key guard apply acceptance time,
+    /// activate on release and first touch only.
+    pub fn touch_down(
+        &mut self,
+        id: u32,
+        cell: Option<(usize, usize)>,
+    ) -> Result<Vec<SessionEvent>> {
+        self.record(RecordedInput::TouchDown { id, cell });
+        let accepted = self.guard.touch_down(id, cell, self.scan.now());
+        self.dispatch_all(accepted)
+    }
+
+    /// Handle a touch sliding onto another cell (or off the grid).
+    pub fn touch_move(
+        &mut self,
+        id: u32,
+        cell: Option<(usize, usize)>,
+    ) -> Result<Vec<SessionEvent>> {
+        self.record(RecordedInput::TouchMove { id, cell });
+        let accepted = self.guard.touch_move(id, cell, self.scan.now());
+        self.dispatch_all(accepted)
+    }
+
+    /// Handle a touch being lifted.
+    pub fn touch_up(&mut self, id: u32) -> Result<Vec<SessionEvent>> {
+        self.record(RecordedInput::TouchUp { id });
+        let accepted = self.guard.touch_up(id, self.scan.now());
+        self.dispatch_all(accepted)
+    }
+
+    fn dispatch_all(&mut self, inputs: Vec<InputEvent>) -> Result<Vec<SessionEvent>> {
+        let mut events = Vec::new();
+        for input in inputs {
+            let paused = self.router.scan_paused_until();
+            let Some(input) = self.router.route(input, self.scan.now()) else {
+                continue;
+            };
+            events.extend(self.dispatch(input)?);
+
+            // A direct selection holds the scan highlight still for a while
+            let resume = self.router.scan_paused_until();
+            if let (Some(until), true) = (resume, resume != paused) {
+                self.scan.reschedule(until);
+            }
+        }
+        Ok(events)
+    }
+
+    fn dispatch(&mut self, event: InputEvent) -> Result<Vec<SessionEvent>> {
+        let mut events = Vec::new();
+
+        match event {
+            InputEvent::Tap { row, col } | InputEvent::Dwell { row, col, .. } => {
+                if self.is_cancel_cell(row, col) {
+                    self.scan_rate.on_cancel(self.scan.now());
+                }
+                self.activate(row, col, &mut events)?;
+            }
+
+            InputEvent::SwitchPress { switch_id } if self.morse.is_some() => {
+                // Time the hold from the physical press, not from when the
+                // key guard accepted it
+                let pressed = self.guard.press_time(switch_id);
+                self.morse_input(&mut events, |morse, now| {
+                    morse.on_switch_down(switch_id, pressed.unwrap_or(now))
+                })?;
+            }
+
+            InputEvent::SwitchRelease { switch_id } if self.morse.is_some() => {
+                self.morse_input(&mut events, |morse, now| {
+                    morse.on_switch_up(switch_id, now);
+                    None
+                })?;
+            }
+
+            InputEvent::SwitchPress { switch_id } => {
+                let now = self.scan.now();
+                let timed = self.scan.next_due().is_some();
+                let action = self.scan.switch_action(switch_id);
+                let outcome = self.scan.on_switch_down(switch_id, now);
+                match action {
+                    Some(SwitchAction::Select) if timed => self.scan_rate.on_select(now),
+                    Some(SwitchAction::Back) => self.scan_rate.on_cancel(now),
+                    _ => {}
+                }
+                self.handle_switch(outcome, &mut events)?;
+                self.tune_scan_rate(&mut events);
+            }
+
+            InputEvent::SwitchRelease { switch_id } => {
+                let outcome = self.scan.on_switch_up(switch_id, self.scan.now());
+                self.handle_switch(outcome, &mut events)?;
+            }
+
+            InputEvent::Key(key) => {
+                if key == KeyEvent::Escape {
+                    self.scan_rate.on_cancel(self.scan.now());
+                }
+                self.handle_key(key, &mut events)?;
+            }
+
+            InputEvent::Voice { command } => self.handle_voice(&command, &mut events)?,
+
+            InputEvent::DoubleTap { .. }
+            | InputEvent::LongPress { .. }
+            | InputEvent::Scroll { .. } => {}
+        }
+
+        Ok(events)
+    }
+
+    /// Advance the scanner one step (for the host's scan timer).
+    pub fn advance_scan(&mut self) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        let before = self.scan.state();
+        self.scan.scanner_mut().advance();
+        if self.scan.state() != before {
+            self.scan_changed(&mut events);
+        }
+        events
+    }
+
+    /// Move the scan highlight if it is due, using the session's clock.
+    ///
+    /// Call this regularly (e.g. every frame) instead of
+    /// [`advance_scan`](Self::advance_scan) to get the configured scan timing.
+    ///
+    /// Touches and switch presses held back by the key guard are also
+    /// accepted here once their acceptance time has passed.
+    pub fn tick(&mut self) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        let before = self
+            .recording
+            .as_ref()
+            .map(|_| self.snapshot(RecordedInput::Tick));
+        let accepted = self.guard.update(self.scan.now());
+        match self.dispatch_all(accepted) {
+            Ok(accepted) => events.extend(accepted),
+            Err(e) => log::warn!("Failed to handle held input: {}", e),
+        }
+        if let Err(e) = self.morse_input(&mut events, MorseDecoder::update) {
+            log::warn!("Failed to handle Morse input: {}", e);
+        }
+        if self.scan.update().is_some() {
+            self.scan_changed(&mut events);
+        }
+
+        // Only ticks that did something are needed to replay the session
+        if let (Some(record), Some(recording), false) =
+            (before, self.recording.as_mut(), events.is_empty())
+        {
+            recording.push(record);
+        }
+        events
+    }
+
+    /// Apply the scan interval suggested by the tuner, e.g. when a
+    /// caregiver accepts a [`SessionEvent::ScanIntervalSuggested`].
+    ///
+    /// Returns the new interval, if there was a suggestion.
+    pub fn accept_scan_interval(&mut self) -> Option<Duration> {
+        let interval = self.scan_rate.apply()?;
+        self.set_scan_interval(interval);
+        Some(interval)
+    }
+
+    /// Save the current scan interval, including what the tuner learned,
+    /// to a profile's settings.
+    pub fn save_scan_interval(&self, settings: &mut AccessibilitySettings) {
+        settings.scan_interval = self.scan.scanner().config().scan_interval;
+    }
+
+    /// Start recording input, discarding any earlier recording.
+    ///
+    /// Input events, raw touches and ticks are recorded; scan steps made
+    /// with [`advance_scan`](Self::advance_scan) are not.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new());
+    }
+
+    /// Get the input recorded so far, if recording.
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Stop recording and return what was recorded.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    fn record(&mut self, input: RecordedInput) {
+        if self.recording.is_some() {
+            let record = self.snapshot(input);
+            if let Some(recording) = self.recording.as_mut() {
+                recording.push(record);
+            }
+        }
+    }
+
+    fn snapshot(&self, input: RecordedInput) -> InputRecord {
+        InputRecord {
+            time: self.scan.now(),
+            board_id: self.navigator.current().id.clone(),
+            scan_state: self.scan.state(),
+            input,
+        }
+    }
+
+    /// Activate the cell at a position.
+    ///
+    /// Empty positions and hidden buttons are ignored.
+    pub fn activate_cell(&mut self, row: usize, col: usize) -> Result<Vec<SessionEvent>> {
+        let mut events = Vec::new();
+        self.activate(row, col, &mut events)?;
+        Ok(events)
+    }
+
+    /// Execute a cell action that didn't come from a button.
+    pub fn execute(&mut self, action: CellAction) -> Result<Vec<SessionEvent>> {
+        let mut events = Vec::new();
+        self.run_action(action, None, &mut events)?;
+        Ok(events)
+    }
+
+    /// Speak the whole message bar.
+    pub fn speak_message(&mut self) -> Result<Vec<SessionEvent>> {
+        match self.message.speak_all(self.speech.as_ref(), &self.voice)? {
+            Some(text) => Ok(vec![SessionEvent::Spoke(text)]),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn handle_voice(&mut self, phrase: &str, events: &mut Vec<SessionEvent>) -> Result<()> {
+        let target = match self.voice_grammar.parse(phrase) {
+            Some(VoiceCommand::Back) => Some(CellAction::Back),
+            Some(VoiceCommand::Home) => Some(CellAction::Home),
+            Some(VoiceCommand::Clear) => Some(CellAction::Clear),
+            Some(VoiceCommand::Speak) => {
+                events.extend(self.speak_message()?);
+                return Ok(());
+            }
+            Some(VoiceCommand::Say(label)) => {
+                if let Some((row, col)) = self.find_cell(&label) {
+                    return self.activate(row, col, events);
+                }
+                None
+            }
+            Some(VoiceCommand::Open(name)) => self.find_board(&name).map(CellAction::Navigate),
+            None => None,
+        };
+
+        match target {
+            Some(action) => self.run_action(action, None, events),
+            None => {
+                events.push(SessionEvent::VoiceUnrecognized(phrase.to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Find the visible cell on the current board whose label best matches.
+    fn find_cell(&self, label: &str) -> Option<(usize, usize)> {
+        let board = self.navigator.current();
+        let cells = (0..board.grid.rows)
+            .flat_map(|row| (0..board.grid.columns).map(move |col| (row, col)))
+            .filter_map(|(row, col)| {
+                let button = board.button_at(row, col).filter(|b| !b.hidden)?;
+                Some((button.label.as_str(), (row, col)))
+            });
+        self.voice_grammar.best_match(label, cells)
+    }
+
+    /// Find the stored board whose name best matches, by ID.
+    fn find_board(&self, name: &str) -> Option<String> {
+        let storage = self.storage.as_ref()?;
+        let ids = storage
+            .list_boards()
+            .inspect_err(|e| log::warn!("Failed to list boards: {}", e))
+            .ok()?;
+        let boards: Vec<_> = ids
+            .iter()
+            .filter_map(|id| storage.load_board(id).ok())
+            .map(|board| (board.name, board.id))
+            .collect();
+        self.voice_grammar.best_match(name, boards)
+    }
+
+    fn activate(&mut self, row: usize, col: usize, events: &mut Vec<SessionEvent>) -> Result<()> {
+        let Some(button) = self.navigator.current().button_at(row, col) else {
+            return Ok(());
+        };
+        let cell = Cell::new(button, row, col);
+        if cell.is_hidden() {
+            return Ok(());
+        }
+
+        let actions = cell.actions();
+        let origin = Origin {
+            button_id: cell.id().to_string(),
+            image_id: cell.image_id().map(str::to_string),
+            load_board: button.load_board.clone(),
+        };
+        self.usage.record(&origin.button_id);
+        events.push(SessionEvent::CellActivated {
+            row,
+            col,
+            button_id: origin.button_id.clone(),
+        });
+        for action in actions {
+            self.run_action(action, Some(origin.clone()), events)?;
+        }
+        Ok(())
+    }
+
+    fn run_action(
+        &mut self,
+        action: CellAction,
+        origin: Option<Origin>,
+        events: &mut Vec<SessionEvent>,
+    ) -> Result<()> {
+        match action {
+            // Phrases spoken from a button also go to the message bar so the
+            // user can build on them; direct speech requests don't.
+            CellAction::Speak(text) => {
+                if let Some(origin) = origin {
+                    self.message.append(origin.token(&text));
+                    events.push(SessionEvent::MessageChanged(self.message()));
+                }
+                if self.speak_mode == SpeakMode::SpeakOnSelect {
+                    self.speech.speak(&text, &self.voice)?;
+                    events.push(SessionEvent::Spoke(text));
+                }
+            }
+
+            CellAction::Navigate(_) | CellAction::NavigatePath(_) | CellAction::NavigateUrl(_) => {
+                // The button's own link can name an id, path and url at once
+                let link = origin
+                    .and_then(|origin| origin.load_board)
+                    .or_else(|| action.load_board());
+                if let Some(link) = link {
+                    let before = self.navigator.current().id.clone();
+                    self.navigator.navigate(&link, self.resolver.as_ref())?;
+                    self.after_board_change(before, events);
+                }
+            }
+
+            CellAction::Back => {
+                let before = self.navigator.current().id.clone();
+                self.navigator.try_pop();
+                self.after_board_change(before, events);
+            }
+
+            CellAction::Home => {
+                let before = self.navigator.current().id.clone();
+                self.navigator.go_home();
+                self.after_board_change(before, events);
+            }
+
+            CellAction::Clear => {
+                if !self.message.is_empty() {
+                    self.message.clear();
+                    events.push(SessionEvent::MessageChanged(self.message()));
+                }
+            }
+
+            CellAction::Backspace => {
+                if self.message.backspace() {
+                    events.push(SessionEvent::MessageChanged(self.message()));
+                }
+            }
+
+            CellAction::AppendText(text) => {
+                self.message.append_text(&text);
+                events.push(SessionEvent::MessageChanged(self.message()));
+            }
+
+            CellAction::Space => {
+                if self.message.space() {
+                    events.push(SessionEvent::MessageChanged(self.message()));
+                }
+            }
+
+            CellAction::AddWord(word) => {
+                let token = match origin {
+                    Some(origin) => origin.token(&word),
+                    None => MessageToken::word(word),
+                };
+                self.message.append(token);
+                events.push(SessionEvent::MessageChanged(self.message()));
+            }
+
+            CellAction::PlaySound(sound_id) => events.push(SessionEvent::PlaySound(sound_id)),
+
+            CellAction::Extension { namespace, name } => {
+                events.push(SessionEvent::Extension { namespace, name })
+            }
+
+            CellAction::Custom(name) => events.push(SessionEvent::Custom(name)),
+        }
+        Ok(())
+    }
+
+    /// Resize the scanner and report the change if the board is different.
+    fn after_board_change(&mut self, before: String, events: &mut Vec<SessionEvent>) {
+        let current = self.navigator.current();
+        if current.id == before {
+            return;
+        }
+
+        let board_id = current.id.clone();
+        let was_scanning = self.scan.state() != ScanState::Idle;
+        self.fit_scanner();
+        events.push(SessionEvent::BoardChanged { board_id });
+        if was_scanning {
+            self.scan.start();
+            self.scan_changed(events);
+        }
+    }
+
+    /// Fit the scanner to the current board.
+    ///
+    /// Scan weights are only refreshed here, so the frequency order stays
+    /// the same while the user is on a board.
+    fn fit_scanner(&mut self) {
+        let board = self.navigator.current();
+        let scanner = self.scan.scanner_mut();
+        scanner.set_board(board);
+        scanner.set_weights(self.usage.scan_weights(board));
+        self.scan.stop();
+    }
+
+    fn handle_switch(
+        &mut self,
+        outcome: SwitchOutcome,
+        events: &mut Vec<SessionEvent>,
+    ) -> Result<()> {
+        match outcome {
+            SwitchOutcome::Ignored => {}
+            SwitchOutcome::Moved(_) => self.scan_changed(events),
+            SwitchOutcome::Selected { row, col } => {
+                events.push(SessionEvent::ScanChanged(self.scan.state()));
+                self.activate(row, col, events)?;
+            }
+            SwitchOutcome::Speak(state) => {
+                let text = state
+                    .highlighted_position()
+                    .and_then(|(row, col)| self.navigator.current().button_at(row, col))
+                    .map(|button| button.speak_text().to_string());
+                if let Some(text) = text {
+                    self.speech.speak(&text, &self.voice)?;
+                    events.push(SessionEvent::Spoke(text));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply or suggest a new scan interval, as the profile asks.
+    fn tune_scan_rate(&mut self, events: &mut Vec<SessionEvent>) {
+        match self.scan_rate.settings().mode {
+            ScanRateMode::Off => {}
+            ScanRateMode::Suggest => {
+                if let Some(interval) = self.scan_rate.new_suggestion() {
+                    events.push(SessionEvent::ScanIntervalSuggested(interval));
+                }
+            }
+            ScanRateMode::Automatic => {
+                if let Some(interval) = self.scan_rate.apply() {
+                    self.set_scan_interval(interval);
+                    events.push(SessionEvent::ScanIntervalChanged(interval));
+                }
+            }
+        }
+    }
+
+    fn set_scan_interval(&mut self, interval: Duration) {
+        let scanner = self.scan.scanner_mut();
+        let mut config = scanner.config().clone();
+        config.scan_interval = interval;
+        *scanner = scanner.clone().with_config(config);
+    }
+
+    /// Check if a cell undoes the last selection (back or backspace).
+    fn is_cancel_cell(&self, row: usize, col: usize) -> bool {
+        self.navigator
+            .current()
+            .button_at(row, col)
+            .is_some_and(|button| {
+                Cell::new(button, row, col)
+                    .actions()
+                    .iter()
+                    .any(|a| matches!(a, CellAction::Back | CellAction::Backspace))
+            })
+    }
+
+    /// Report the scan state and announce it if auditory scanning is on.
+    ///
+    /// Prompt failures are logged rather than interrupting scanning.
+    fn scan_changed(&mut self, events: &mut Vec<SessionEvent>) {
+        events.push(SessionEvent::ScanChanged(self.scan.state()));
+        self.scan_rate.on_highlight(self.scan.now());
+
+        let (Some(prompter), Some(announcement)) =
+            (&self.prompter, self.scan.scanner().announcement())
+        else {
+            return;
+        };
+        match prompter.announce(
+            self.speech.as_ref(),
+            &announcement,
+            self.navigator.current(),
+        ) {
+            Ok(Some(text)) => events.push(SessionEvent::Announced(text)),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to announce scan prompt: {}", e),
+        }
+    }
+
+    /// Feed the Morse decoder, apply what it decoded, and report changes
+    /// to the pending code.
+    fn morse_input(
+        &mut self,
+        events: &mut Vec<SessionEvent>,
+        input: impl FnOnce(&mut MorseDecoder, Duration) -> Option<MorseOutput>,
+    ) -> Result<()> {
+        let now = self.scan.now();
+        let Some(morse) = &mut self.morse else {
+            return Ok(());
+        };
+        let before = morse.code().to_string();
+        let output = input(morse, now);
+        let code = morse.code().to_string();
+
+        match output {
+            Some(MorseOutput::Char(ch)) => {
+                self.run_action(CellAction::AppendText(ch.to_string()), None, events)?
+            }
+            Some(MorseOutput::Command(MorseCommand::Space)) => {
+                self.run_action(CellAction::Space, None, events)?
+            }
+            Some(MorseOutput::Command(MorseCommand::Backspace)) => {
+                self.run_action(CellAction::Backspace, None, events)?
+            }
+            Some(MorseOutput::Command(MorseCommand::Clear)) => {
+                self.run_action(CellAction::Clear, None, events)?
+            }
+            Some(MorseOutput::Command(MorseCommand::Speak)) => events.extend(self.speak_message()?),
+            Some(MorseOutput::Unknown(code)) => log::debug!("Unknown Morse code {}", code),
+            None => {}
+        }
+
+        if code != before {
+            events.push(SessionEvent::MorseChanged(code));
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, events: &mut Vec<SessionEvent>) -> Result<()> {
+        match self.keys.handle(key, &mut self.navigator) {
+            KeyOutcome::Moved(Focus::Cell { row, col }) => {
+                events.push(SessionEvent::CursorMoved(Some((row, col))));
+            }
+            KeyOutcome::Moved(focus) => events.push(SessionEvent::FocusChanged(focus)),
+            KeyOutcome::Unchanged => {}
+            KeyOutcome::Activate(Focus::Cell { row, col }) => self.activate(row, col, events)?,
+            KeyOutcome::Activate(Focus::MessageBar) => events.extend(self.speak_message()?),
+            KeyOutcome::Activate(Focus::Toolbar(index)) => {
+                if let Some(action) = self.keys.config().toolbar.get(index).cloned() {
+                    self.run_action(action, None, events)?;
+                }
+            }
+            KeyOutcome::Back => self.run_action(CellAction::Back, None, events)?,
+        }
+        Ok(())
+    }
+}
+
+/// The button an action came from, used to label message bar tokens and
+/// to follow its board link with every target it names.
+#[derive(Clone)]
+struct Origin {
+    button_id: String,
+    image_id: Option<String>,
+    load_board: Option<ObfLoadBoard>,
+}
+
+impl Origin {
+    fn token(&self, text: &str) -> MessageToken {
+        MessageToken::from_button(text, self.button_id.clone(), self.image_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::board::{DataUrlResolver, ResolverChain, StorageResolver};
+    use crate::input::{
+        BindingTarget, KeyboardConfig, ManualClock, ScanMode, ScanOrder, ScanTechnique,
+    };
+    use crate::obf::ObfButton;
+    use crate::speech::MemorySpeech;
+    use crate::storage::{MemoryStorage, MorseMode};
+
+    fn setup() -> (Session, Arc<MemorySpeech>) {
+        let mut home = ObfBoard::new("home", 2, 2);
+        home.add_button(ObfButton::speak("hello", "Hello"));
+        home.add_button(ObfButton::navigate("more", "More", "sub"));
+        home.place_button_at("hello", 0, 0);
+        home.place_button_at("more", 0, 1);
+
+        let mut sub = ObfBoard::new("sub", 1, 3);
+        sub.add_button(ObfButton::speak("bye", "Goodbye"));
+        sub.add_button(ObfButton::back("back"));
+        sub.place_button_at("bye", 0, 0);
+        sub.place_button_at("back", 0, 2);
+
+        let storage = Arc::new(MemoryStorage::with_boards(vec![sub]));
+        let resolver = Arc::new(ResolverChain::new().with(StorageResolver::new(storage)));
+        let speech = Arc::new(MemorySpeech::new());
+        (Session::new(home, speech.clone(), resolver), speech)
+    }
+
+    #[test]
+    fn test_tap_speaks() {
+        let (mut session, speech) = setup();
+        let events = session.handle_input(InputEvent::tap(0, 0)).unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                SessionEvent::CellActivated {
+                    row: 0,
+                    col: 0,
+                    button_id: "hello".to_string()
+                },
+                SessionEvent::MessageChanged("Hello".to_string()),
+                SessionEvent::Spoke("Hello".to_string()),
+            ]
+        );
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+        assert_eq!(
+            session.message_bar().tokens()[0].button_id.as_deref(),
+            Some("hello")
+        );
+    }
+
+    #[test]
+    fn test_sound_button_keeps_label() {
+        let mut home = ObfBoard::new("home", 1, 1);
+        let mut laugh = ObfButton::new("laugh", "Laugh");
+        laugh.sound_id = Some("snd_laugh".to_string());
+        home.add_button(laugh);
+        home.place_button_at("laugh", 0, 0);
+        let speech = Arc::new(MemorySpeech::new());
+        let mut session = Session::new(home, speech.clone(), Arc::new(ResolverChain::new()));
+
+        let events = session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        assert!(events.contains(&SessionEvent::PlaySound("snd_laugh".to_string())));
+        assert_eq!(session.message(), "Laugh");
+        assert_eq!(speech.spoken(), vec!["Laugh"]);
+    }
+
+    #[test]
+    fn test_build_sentence_mode() {
+        let (session, speech) = setup();
+        let mut session = session.with_speak_mode(SpeakMode::BuildSentence);
+
+        session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        assert!(speech.spoken().is_empty());
+        assert_eq!(session.message(), "Hello Hello");
+
+        session.speak_message().unwrap();
+        assert_eq!(speech.spoken(), vec!["Hello Hello"]);
+    }
+
+    #[test]
+    fn test_speak_mode_from_profile() {
+        let (session, _) = setup();
+        let mut settings = ProfileSettings::default();
+        settings.voice.speak_mode = SpeakMode::BuildSentence;
+
+        let session = session.with_settings(&settings);
+        assert_eq!(session.speak_mode(), SpeakMode::BuildSentence);
+    }
+
+    #[test]
+    fn test_tap_navigates_and_back() {
+        let (mut session, _) = setup();
+
+        let events = session.handle_input(InputEvent::tap(0, 1)).unwrap();
+        assert!(events.contains(&SessionEvent::BoardChanged {
+            board_id: "sub".to_string()
+        }));
+        assert_eq!(session.current_board().id, "sub");
+        // The scanner now covers sub's 1x3 grid rather than home's 2x2
+        assert!(session.scanner().is_scannable(0, 2));
+        assert!(!session.scanner().is_scannable(1, 0));
+
+        session.handle_input(InputEvent::tap(0, 2)).unwrap();
+        assert_eq!(session.current_board().id, "home");
+    }
+
+    #[test]
+    fn test_button_link_keeps_every_target() {
+        let mut home = ObfBoard::new("home", 1, 1);
+        let mut inline = ObfButton::navigate("inline", "Inline", "not-stored");
+        let board = serde_json::to_string(&ObfBoard::new("not-stored", 1, 1)).unwrap();
+        inline.load_board.as_mut().unwrap().data_url =
+            Some(format!("data:application/json,{}", board));
+        home.add_button(inline);
+        home.place_button_at("inline", 0, 0);
+
+        // The id is unknown to storage, so only the data URL can resolve it
+        let storage = Arc::new(MemoryStorage::new());
+        let resolver = Arc::new(
+            ResolverChain::new()
+                .with(StorageResolver::new(storage))
+                .with(DataUrlResolver),
+        );
+        let mut session = Session::new(home, Arc::new(MemorySpeech::new()), resolver);
+        session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        assert_eq!(session.current_board().id, "not-stored");
+    }
+
+    #[test]
+    fn test_empty_cell_ignored() {
+        let (mut session, speech) = setup();
+        let events = session.handle_input(InputEvent::tap(1, 1)).unwrap();
+        assert!(events.is_empty());
+        assert!(speech.spoken().is_empty());
+    }
+
+    #[test]
+    fn test_message_actions() {
+        let (mut session, speech) = setup();
+
+        session
+            .execute(CellAction::AddWord("I".to_string()))
+            .unwrap();
+        session
+            .execute(CellAction::AddWord("love".to_string()))
+            .unwrap();
+        assert_eq!(session.message(), "I love");
+
+        let events = session.execute(CellAction::Backspace).unwrap();
+        assert_eq!(events, vec![SessionEvent::MessageChanged("I".to_string())]);
+
+        session.speak_message().unwrap();
+        assert_eq!(speech.last_spoken(), Some("I".to_string()));
+
+        session.execute(CellAction::Clear).unwrap();
+        assert_eq!(session.message(), "");
+    }
+
+    #[test]
+    fn test_speak_then_navigate() {
+        let (mut session, speech) = setup();
+        let mut hungry = ObfButton::navigate("hungry", "I'm hungry", "sub");
+        hungry.action = Some(":speak".to_string());
+        let home = session.navigator.current_mut();
+        home.add_button(hungry);
+        home.place_button_at("hungry", 1, 0);
+
+        let events = session.handle_input(InputEvent::tap(1, 0)).unwrap();
+        assert_eq!(speech.spoken(), vec!["I'm hungry"]);
+        assert_eq!(session.current_board().id, "sub");
+        assert_eq!(
+            events.last(),
+            Some(&SessionEvent::BoardChanged {
+                board_id: "sub".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_spelling_keyboard() {
+        let mut keyboard = ObfBoard::new("keyboard", 1, 4);
+        for (id, action) in [
+            ("h", "+h"),
+            ("i", "+i"),
+            ("space", ":space"),
+            ("del", ":backspace"),
+        ] {
+            let mut key = ObfButton::new(id, id);
+            key.action = Some(action.to_string());
+            keyboard.add_button(key);
+        }
+        keyboard.place_button_at("h", 0, 0);
+        keyboard.place_button_at("i", 0, 1);
+        keyboard.place_button_at("space", 0, 2);
+        keyboard.place_button_at("del", 0, 3);
+
+        let speech = Arc::new(MemorySpeech::new());
+        let mut session = Session::new(keyboard, speech.clone(), Arc::new(ResolverChain::new()));
+
+        for col in [0, 1, 1, 3, 2, 0] {
+            session.handle_input(InputEvent::tap(0, col)).unwrap();
+        }
+        assert_eq!(session.message(), "hi h");
+        assert!(speech.spoken().is_empty());
+    }
+
+    #[test]
+    fn test_switch_scanning_selects() {
+        let (mut session, speech) = setup();
+
+        // First press starts scanning, second picks row 0, third picks column 0
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        let events = session.handle_input(InputEvent::switch_press()).unwrap();
+
+        assert_eq!(
+            events[0],
+            SessionEvent::ScanChanged(ScanState::Selected { row: 0, col: 0 })
+        );
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_step_scanning_from_settings() {
+        let (session, speech) = setup();
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        settings.input.primary_switch_action = SwitchAction::Next;
+        settings.input.secondary_switch_action = SwitchAction::Select;
+        settings.input.scan_technique = ScanTechnique::Step;
+        let mut session = session.with_settings(&settings);
+
+        // Switch 1 starts and steps, switch 2 selects
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        session
+            .handle_input(InputEvent::switch_secondary())
+            .unwrap();
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        session
+            .handle_input(InputEvent::switch_secondary())
+            .unwrap();
+
+        assert!(speech.spoken().is_empty());
+        assert_eq!(session.current_board().id, "sub");
+    }
+
+    #[test]
+    fn test_tick_advances_with_clock() {
+        let (session, _) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut session = session.with_clock(clock.clone());
+
+        // Start scanning and pick the only row
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(
+            session.tick(),
+            vec![SessionEvent::ScanChanged(ScanState::ScanningColumns {
+                row: 0,
+                current_col: 1
+            })]
+        );
+        assert!(session.tick().is_empty());
+    }
+
+    #[test]
+    fn test_auditory_scanning() {
+        let (session, speech) = setup();
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        settings.accessibility.auditory_scanning = true;
+        let mut session = session.with_settings(&settings);
+
+        // Rows and cells are announced quietly
+        let events = session.handle_input(InputEvent::switch_press()).unwrap();
+        assert_eq!(events[1], SessionEvent::Announced("Hello".to_string()));
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        session.advance_scan();
+        assert_eq!(speech.last_spoken().as_deref(), Some("More"));
+        assert_eq!(speech.utterances()[0].1.volume, 0.6);
+
+        // The selection is spoken in the output voice
+        session.scanner_mut().retreat();
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        let (text, voice) = speech.utterances().pop().unwrap();
+        assert_eq!(text, "Hello");
+        assert_eq!(voice.volume, 1.0);
+    }
+
+    #[test]
+    fn test_frequency_scan_order() {
+        let (session, _) = setup();
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        settings.accessibility.scan_mode = ScanMode::Linear;
+        settings.accessibility.scan_order = ScanOrder::Frequency;
+        let clock = Arc::new(ManualClock::new());
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // Usage is recorded, but the order only changes with the board
+        session.handle_input(InputEvent::tap(0, 1)).unwrap();
+        session.handle_input(InputEvent::tap(0, 2)).unwrap();
+        assert_eq!(session.usage().count("more"), 1);
+
+        clock.advance(Duration::from_secs(1));
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        assert_eq!(
+            session.scanner().state().highlighted_position(),
+            Some((0, 1))
+        );
+    }
+
+    #[test]
+    fn test_input_routing() {
+        let (session, speech) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // Dwell isn't enabled in the profile
+        let dwell = InputEvent::dwell(0, 0, Duration::from_secs(1));
+        assert!(session.handle_input(dwell).unwrap().is_empty());
+
+        // A tap while scanning holds the highlight still
+        for _ in 0..2 {
+            session.handle_input(InputEvent::switch_press()).unwrap();
+            session
+                .handle_input(InputEvent::SwitchRelease { switch_id: 0 })
+                .unwrap();
+        }
+        clock.advance(Duration::from_millis(1000));
+        session.handle_input(InputEvent::tap(1, 1)).unwrap();
+        clock.advance(Duration::from_millis(2000));
+        assert!(session.tick().is_empty());
+        assert_eq!(
+            session.scanner().state(),
+            ScanState::ScanningColumns {
+                row: 0,
+                current_col: 0
+            }
+        );
+
+        // A switch press right after a tap is dropped
+        session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        assert!(session
+            .handle_input(InputEvent::switch_press())
+            .unwrap()
+            .is_empty());
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_keyboard_activation_is_routed() {
+        let (session, speech) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // Space activates the focused cell like Enter does
+        session
+            .handle_input(InputEvent::Key(KeyEvent::Down))
+            .unwrap();
+        session
+            .handle_input(InputEvent::Key(KeyEvent::Space))
+            .unwrap();
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+
+        // A switch press inside the conflict window is dropped
+        clock.advance(Duration::from_millis(200));
+        assert!(session
+            .handle_input(InputEvent::switch_press())
+            .unwrap()
+            .is_empty());
+        assert_eq!(session.scanner().state(), ScanState::Idle);
+        assert!(session.router().is_scan_paused(clock.now()));
+    }
+
+    #[test]
+    fn test_voice_commands() {
+        let (session, speech) = setup();
+        let mut feelings = ObfBoard::new("feelings", 1, 1);
+        feelings.name = "Feelings".to_string();
+        let storage = Arc::new(MemoryStorage::with_boards(vec![feelings]));
+        let resolver = Arc::new(ResolverChain::new().with(StorageResolver::new(storage.clone())));
+        let home = session.current_board().clone();
+        let mut session = Session::new(home, speech.clone(), resolver).with_storage(storage);
+        let voice = |command: &str| InputEvent::Voice {
+            command: command.to_string(),
+        };
+
+        // Labels are matched fuzzily
+        session.handle_input(voice("say helo")).unwrap();
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+        session.handle_input(voice("clear")).unwrap();
+        assert!(session.message().is_empty());
+
+        let events = session.handle_input(voice("open feelins")).unwrap();
+        assert!(events.contains(&SessionEvent::BoardChanged {
+            board_id: "feelings".to_string()
+        }));
+        session.handle_input(voice("go back")).unwrap();
+        assert_eq!(session.current_board().id, "home");
+
+        assert_eq!(
+            session.handle_input(voice("say pizza")).unwrap(),
+            vec![SessionEvent::VoiceUnrecognized("say pizza".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let (session, _) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+        session.start_recording();
+        let click = |session: &mut Session| {
+            session.handle_input(InputEvent::switch_press()).unwrap();
+            session
+                .handle_input(InputEvent::SwitchRelease { switch_id: 0 })
+                .unwrap();
+        };
+
+        // Scan to "More", then say "Goodbye" on the next board
+        click(&mut session);
+        click(&mut session);
+        for _ in 0..4 {
+            clock.advance(Duration::from_millis(500));
+            session.tick();
+        }
+        click(&mut session);
+        clock.advance(Duration::from_secs(1));
+        session.handle_input(InputEvent::tap(0, 0)).unwrap();
+        assert_eq!(session.current_board().id, "sub");
+
+        // Only the tick that moved the highlight is recorded
+        let recording = session.stop_recording().unwrap();
+        assert_eq!(recording.len(), 8);
+        let recording = Recording::from_jsonl(&recording.to_jsonl().unwrap()).unwrap();
+
+        let (fresh, speech) = setup();
+        let mut replayer = Replayer::new(fresh.with_settings(&settings));
+        let report = replayer.run(&recording).unwrap();
+        assert!(!report.diverged());
+        assert!(report.events().any(|e| e
+            == &SessionEvent::BoardChanged {
+                board_id: "sub".to_string()
+            }));
+        assert_eq!(speech.spoken(), vec!["Goodbye"]);
+        assert_eq!(replayer.session().current_board().id, "sub");
+
+        // Replaying into a session on another board is flagged
+        let (mut other, _) = setup();
+        other.handle_input(InputEvent::tap(0, 1)).unwrap();
+        let report = Replayer::new(other).run(&recording).unwrap();
+        assert_eq!(report.first_divergence(), Some(0));
+    }
+
+    #[test]
+    fn test_adaptive_scan_rate() {
+        let (session, _) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        settings.accessibility.scan_mode = ScanMode::Linear;
+        settings.accessibility.scan_rate.mode = ScanRateMode::Automatic;
+        settings.accessibility.scan_rate.samples = 3;
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // The user needs 900ms of the 1000ms interval to react
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            for wait in [0, 800] {
+                clock.advance(Duration::from_millis(wait));
+                events.extend(session.handle_input(InputEvent::switch_press()).unwrap());
+                clock.advance(Duration::from_millis(100));
+                session
+                    .handle_input(InputEvent::SwitchRelease { switch_id: 0 })
+                    .unwrap();
+            }
+            clock.advance(Duration::from_secs(3));
+        }
+        let interval = Duration::from_millis(1130);
+        assert!(events.contains(&SessionEvent::ScanIntervalChanged(interval)));
+        assert_eq!(session.scanner().config().scan_interval, interval);
+
+        // The learned interval is saved back to the profile
+        session.save_scan_interval(&mut settings.accessibility);
+        assert_eq!(settings.accessibility.scan_interval, interval);
+    }
+
+    #[test]
+    fn test_key_guard_from_settings() {
+        let (session, speech) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.input.key_guard.acceptance_time = Duration::from_millis(300);
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // A brief touch is ignored
+        session.touch_down(0, Some((0, 0))).unwrap();
+        clock.advance(Duration::from_millis(100));
+        assert!(session.touch_up(0).unwrap().is_empty());
+
+        // A held touch selects once the acceptance time has passed
+        session.touch_down(0, Some((0, 0))).unwrap();
+        clock.advance(Duration::from_millis(300));
+        let events = session.tick();
+        assert!(events.contains(&SessionEvent::Spoke("Hello".to_string())));
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_physical_input_bindings() {
+        let (session, speech) = setup();
+        let mut settings = ProfileSettings::default();
+        settings.accessibility.switch_scanning_enabled = true;
+        settings.input.bindings = BindingMap::new()
+            .with_binding(PhysicalInput::GamepadButton(3), BindingTarget::Switch(5))
+            .with_switch_action(5, SwitchAction::Select);
+        let mut session = session.with_settings(&settings);
+
+        // Switch 5 starts scanning, then picks the row and the first cell
+        for _ in 0..3 {
+            session
+                .press_input(&PhysicalInput::GamepadButton(3))
+                .unwrap();
+            session
+                .release_input(&PhysicalInput::GamepadButton(3))
+                .unwrap();
+        }
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+
+        // Unbound inputs do nothing
+        assert!(session
+            .press_input(&PhysicalInput::key("Space"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_space_key_activates_by_default() {
+        let (mut session, speech) = setup();
+
+        // Space is a keyboard key unless bound to a switch
+        session
+            .press_input(&PhysicalInput::key("ArrowDown"))
+            .unwrap();
+        session.press_input(&PhysicalInput::key("Space")).unwrap();
+        assert!(session
+            .release_input(&PhysicalInput::key("Space"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_morse_with_acceptance_time() {
+        let (session, _) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.input.morse.enabled = true;
+        settings.input.morse.mode = MorseMode::SingleSwitch;
+        settings.input.morse.unit = Duration::from_millis(200);
+        settings.input.morse.adaptive = false;
+        settings.input.key_guard.acceptance_time = Duration::from_millis(300);
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        // A 600ms hold is a dash, though the press is only accepted at 300ms
+        session.handle_input(InputEvent::switch_press()).unwrap();
+        clock.advance(Duration::from_millis(300));
+        session.tick();
+        clock.advance(Duration::from_millis(300));
+        session
+            .handle_input(InputEvent::SwitchRelease { switch_id: 0 })
+            .unwrap();
+        clock.advance(Duration::from_millis(600));
+        assert!(session
+            .tick()
+            .contains(&SessionEvent::MessageChanged("t".to_string())));
+    }
+
+    #[test]
+    fn test_morse_spelling() {
+        let (session, speech) = setup();
+        let clock = Arc::new(ManualClock::new());
+        let mut settings = ProfileSettings::default();
+        settings.input.morse.enabled = true;
+        settings.input.morse.mode = MorseMode::TwoSwitch;
+        let mut session = session.with_settings(&settings).with_clock(clock.clone());
+
+        let mut key = |code: &str| {
+            for symbol in code.chars() {
+                let switch_id = if symbol == '.' { 0 } else { 1 };
+                session
+                    .handle_input(InputEvent::SwitchPress { switch_id })
+                    .unwrap();
+                session
+                    .handle_input(InputEvent::SwitchRelease { switch_id })
+                    .unwrap();
+                clock.advance(Duration::from_millis(100));
+            }
+            clock.advance(Duration::from_millis(600));
+            session.tick()
+        };
+
+        // "hi", then space, then speak
+        assert!(key("....").contains(&SessionEvent::MessageChanged("h".to_string())));
+        assert!(key("..").contains(&SessionEvent::MessageChanged("hi".to_string())));
+        key("..--");
+        key(".-.-");
+        assert_eq!(speech.spoken(), vec!["hi"]);
+        assert!(!session.message_bar().is_spelling());
+
+        // Switches no longer drive the scanner
+        assert_eq!(session.scanner().state(), ScanState::Idle);
+    }
+
+    #[test]
+    fn test_keyboard_activation() {
+        let (mut session, speech) = setup();
+
+        let events = session
+            .handle_input(InputEvent::Key(KeyEvent::Down))
+            .unwrap();
+        assert_eq!(events, vec![SessionEvent::CursorMoved(Some((0, 0)))]);
+
+        session
+            .handle_input(InputEvent::Key(KeyEvent::Enter))
+            .unwrap();
+        assert_eq!(speech.spoken(), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_keyboard_regions() {
+        let (session, speech) = setup();
+        let keys = KeyboardNavigator::new(KeyboardConfig {
+            message_bar: true,
+            toolbar: vec![CellAction::Clear],
+            ..KeyboardConfig::default()
+        });
+        let mut session = session.with_keyboard(keys);
+        let mut press = |key| session.handle_input(InputEvent::Key(key)).unwrap();
+
+        // The empty second row is skipped on the way to the toolbar
+        press(KeyEvent::Down);
+        press(KeyEvent::Enter);
+        assert_eq!(
+            press(KeyEvent::Down),
+            vec![SessionEvent::FocusChanged(Focus::Toolbar(0))]
+        );
+        assert!(press(KeyEvent::Enter).contains(&SessionEvent::MessageChanged(String::new())));
+
+        // Activating the message bar speaks it
+        press(KeyEvent::Up);
+        press(KeyEvent::Enter);
+        assert_eq!(
+            press(KeyEvent::Up),
+            vec![SessionEvent::FocusChanged(Focus::MessageBar)]
+        );
+        press(KeyEvent::Enter);
+        assert_eq!(speech.spoken(), vec!["Hello", "Hello", "Hello"]);
+        assert_eq!(session.message(), "Hello");
+    }
+}
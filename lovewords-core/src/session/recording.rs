@@ -0,0 +1,330 @@
+//! Recording and replaying session input.
+//!
+//! A [`Recording`] is the list of inputs a [`Session`] handled, each with
+//! its time and the board and scanner state it arrived in. Recordings are
+//! stored as JSON Lines, one [`InputRecord`] per line, so therapists can
+//! review what a user did and bug reports can be reproduced.
+//!
+//! A [`Replayer`] feeds a recording back into a session on a
+//! [`ManualClock`], so scan timing, key guard delays and Morse pauses
+//! behave exactly as they did, and reports the [`SessionEvent`]s each input
+//! produced. Inputs that arrive in a different board or scanner state than
+//! recorded are flagged as diverged.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use lovewords_core::board::ResolverChain;
+//! use lovewords_core::session::{Recording, Replayer, Session, SessionEvent};
+//! use lovewords_core::speech::MemorySpeech;
+//! use lovewords_core::{InputEvent, ObfBoard, ObfButton};
+//!
+//! let mut home = ObfBoard::new("home", 1, 1);
+//! home.add_button(ObfButton::speak("hi", "Hello"));
+//! home.place_button_at("hi", 0, 0);
+//! let new_session = || {
+//!     let speech = Arc::new(MemorySpeech::new());
+//!     Session::new(home.clone(), speech, Arc::new(ResolverChain::new()))
+//! };
+//!
+//! let mut session = new_session();
+//! session.start_recording();
+//! session.handle_input(InputEvent::tap(0, 0)).unwrap();
+//! let jsonl = session.stop_recording().unwrap().to_jsonl().unwrap();
+//!
+//! let recording = Recording::from_jsonl(&jsonl).unwrap();
+//! let report = Replayer::new(new_session()).run(&recording).unwrap();
+//! assert!(report.events().any(|e| e == &SessionEvent::Spoke("Hello".into())));
+//! assert!(!report.diverged());
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{InputError, Result};
+use crate::input::{InputEvent, ManualClock, ScanState};
+use crate::storage::duration_millis;
+
+use super::{Session, SessionEvent};
+
+/// An input handled by a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedInput {
+    /// An input event, from [`Session::handle_input`].
+    Event(InputEvent),
+
+    /// A touch going down, from [`Session::touch_down`].
+    TouchDown {
+        id: u32,
+        cell: Option<(usize, usize)>,
+    },
+
+    /// A touch moving, from [`Session::touch_move`].
+    TouchMove {
+        id: u32,
+        cell: Option<(usize, usize)>,
+    },
+
+    /// A touch being lifted, from [`Session::touch_up`].
+    TouchUp { id: u32 },
+
+    /// A [`Session::tick`] that changed something.
+    Tick,
+}
+
+/// One line of a recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecord {
+    /// Session clock time when the input arrived.
+    #[serde(rename = "time_ms", with = "duration_millis")]
+    pub time: Duration,
+
+    /// Board shown when the input arrived.
+    pub board_id: String,
+
+    /// Scanner state when the input arrived.
+    pub scan_state: ScanState,
+
+    /// The input.
+    pub input: RecordedInput,
+}
+
+/// Inputs recorded from a session, in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    records: Vec<InputRecord>,
+}
+
+impl Recording {
+    /// Create an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a record.
+    pub fn push(&mut self, record: InputRecord) {
+        self.records.push(record);
+    }
+
+    /// Get the records.
+    pub fn records(&self) -> &[InputRecord] {
+        &self.records
+    }
+
+    /// Get the number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Serialize as JSON Lines, one record per line.
+    ///
+    /// Fails with the line of the first record that can't be serialized.
+    pub fn to_jsonl(&self) -> std::result::Result<String, InputError> {
+        self.records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                serde_json::to_string(record)
+                    .map(|line| line + "\n")
+                    .map_err(|e| InputError::InvalidTrace {
+                        line: index + 1,
+                        message: e.to_string(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Parse JSON Lines written by [`to_jsonl`](Self::to_jsonl).
+    ///
+    /// Blank lines are skipped.
+    pub fn from_jsonl(text: &str) -> std::result::Result<Self, InputError> {
+        let records = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| InputError::InvalidTrace {
+                    line: index + 1,
+                    message: e.to_string(),
+                })
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { records })
+    }
+}
+
+impl FromIterator<InputRecord> for Recording {
+    fn from_iter<I: IntoIterator<Item = InputRecord>>(iter: I) -> Self {
+        Self {
+            records: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// What replaying one record did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayStep {
+    /// The record replayed.
+    pub record: InputRecord,
+
+    /// Events the session produced.
+    pub events: Vec<SessionEvent>,
+
+    /// Whether the session was in a different board or scanner state than
+    /// recorded when the input arrived.
+    pub diverged: bool,
+}
+
+/// What replaying a recording did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    /// One step per record, in order.
+    pub steps: Vec<ReplayStep>,
+}
+
+impl ReplayReport {
+    /// Get every event produced, in order.
+    pub fn events(&self) -> impl Iterator<Item = &SessionEvent> {
+        self.steps.iter().flat_map(|step| &step.events)
+    }
+
+    /// Check if any step diverged from the recording.
+    pub fn diverged(&self) -> bool {
+        self.steps.iter().any(|step| step.diverged)
+    }
+
+    /// Get the index of the first step that diverged.
+    pub fn first_divergence(&self) -> Option<usize> {
+        self.steps.iter().position(|step| step.diverged)
+    }
+}
+
+/// Feeds recorded input back into a session.
+pub struct Replayer {
+    session: Session,
+    clock: Arc<ManualClock>,
+}
+
+impl Replayer {
+    /// Replay into a session, which should start where the recording did.
+    ///
+    /// The session's clock is replaced by a manual clock set to each
+    /// record's time.
+    pub fn new(session: Session) -> Self {
+        let clock = Arc::new(ManualClock::new());
+        Self {
+            session: session.with_clock(clock.clone()),
+            clock,
+        }
+    }
+
+    /// Get the session.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Take back the session.
+    pub fn into_session(self) -> Session {
+        self.session
+    }
+
+    /// Replay a whole recording.
+    pub fn run(&mut self, recording: &Recording) -> Result<ReplayReport> {
+        let steps = recording
+            .records()
+            .iter()
+            .map(|record| self.step(record))
+            .collect::<Result<_>>()?;
+        Ok(ReplayReport { steps })
+    }
+
+    /// Replay a single record.
+    pub fn step(&mut self, record: &InputRecord) -> Result<ReplayStep> {
+        self.clock.set(record.time);
+        let diverged = self.session.current_board().id != record.board_id
+            || self.session.scanner().state() != record.scan_state;
+        if diverged {
+            log::warn!("Replay diverged at {:?}", record.time);
+        }
+
+        let events = match &record.input {
+            RecordedInput::Event(event) => self.session.handle_input(event.clone())?,
+            RecordedInput::TouchDown { id, cell } => self.session.touch_down(*id, *cell)?,
+            RecordedInput::TouchMove { id, cell } => self.session.touch_move(*id, *cell)?,
+            RecordedInput::TouchUp { id } => self.session.touch_up(*id)?,
+            RecordedInput::Tick => self.session.tick(),
+        };
+        Ok(ReplayStep {
+            record: record.clone(),
+            events,
+            diverged,
+        })
+    }
+}
+
+impl std::fmt::Debug for Replayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Replayer")
+            .field("clock", &self.clock)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: u64, input: RecordedInput) -> InputRecord {
+        InputRecord {
+            time: Duration::from_millis(time),
+            board_id: "home".to_string(),
+            scan_state: ScanState::Idle,
+            input,
+        }
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let recording: Recording = [
+            record(0, RecordedInput::Event(InputEvent::tap(0, 1))),
+            record(
+                120,
+                RecordedInput::TouchDown {
+                    id: 3,
+                    cell: Some((1, 1)),
+                },
+            ),
+            record(900, RecordedInput::Tick),
+        ]
+        .into_iter()
+        .collect();
+
+        let jsonl = recording.to_jsonl().unwrap();
+        assert_eq!(jsonl.lines().count(), 3);
+        assert_eq!(
+            jsonl.lines().next(),
+            Some(
+                r#"{"time_ms":0,"board_id":"home","scan_state":"idle","input":{"event":{"tap":{"row":0,"col":1}}}}"#
+            )
+        );
+        assert_eq!(Recording::from_jsonl(&jsonl).unwrap(), recording);
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let jsonl = r#"{"time_ms":0,"board_id":"home","scan_state":"idle","input":"tick"}
+
+{"time_ms":5}"#;
+        let err = Recording::from_jsonl(jsonl).unwrap_err();
+        assert!(matches!(err, InputError::InvalidTrace { line: 3, .. }));
+    }
+}
@@ -0,0 +1,118 @@
+//! In-memory speech engine for testing.
+//!
+//! This implementation records utterances instead of producing audio,
+//! making it ideal for unit tests and headless development.
+
+use std::sync::RwLock;
+
+use crate::error::SpeechError;
+
+use super::r#trait::{SpeechEngine, Voice, VoiceConfig};
+
+/// Speech engine that records what it was asked to say.
+///
+/// Thread-safe via `RwLock`. Speech completes instantly, so
+/// [`is_speaking`](SpeechEngine::is_speaking) is always `false`.
+///
+/// # Example
+///
+/// ```rust
+/// use lovewords_core::speech::MemorySpeech;
+/// use lovewords_core::{SpeechEngine, VoiceConfig};
+///
+/// let engine = MemorySpeech::new();
+/// engine.speak("I love you", &VoiceConfig::default()).unwrap();
+///
+/// assert_eq!(engine.spoken(), vec!["I love you"]);
+/// ```
+#[derive(Debug, Default)]
+pub struct MemorySpeech {
+    utterances: RwLock<Vec<(String, VoiceConfig)>>,
+    stop_count: RwLock<usize>,
+}
+
+impl MemorySpeech {
+    /// Create a new engine with no recorded utterances.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the text of every utterance, oldest first.
+    pub fn spoken(&self) -> Vec<String> {
+        self.utterances
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(text, _)| text.clone())
+            .collect()
+    }
+
+    /// Get every utterance with the voice configuration it was spoken with.
+    pub fn utterances(&self) -> Vec<(String, VoiceConfig)> {
+        self.utterances.read().unwrap().clone()
+    }
+
+    /// Get the most recent utterance, if any.
+    pub fn last_spoken(&self) -> Option<String> {
+        self.utterances
+            .read()
+            .unwrap()
+            .last()
+            .map(|(text, _)| text.clone())
+    }
+
+    /// Get how many times [`stop`](SpeechEngine::stop) was called.
+    pub fn stop_count(&self) -> usize {
+        *self.stop_count.read().unwrap()
+    }
+
+    /// Forget all recorded utterances.
+    pub fn clear(&self) {
+        self.utterances.write().unwrap().clear();
+        *self.stop_count.write().unwrap() = 0;
+    }
+}
+
+impl SpeechEngine for MemorySpeech {
+    fn speak(&self, text: &str, config: &VoiceConfig) -> Result<(), SpeechError> {
+        self.utterances
+            .write()
+            .unwrap()
+            .push((text.to_string(), config.clone()));
+        Ok(())
+    }
+
+    fn stop(&self) {
+        *self.stop_count.write().unwrap() += 1;
+    }
+
+    fn is_speaking(&self) -> bool {
+        false
+    }
+
+    fn list_voices(&self) -> Vec<Voice> {
+        vec![Voice::new("memory", "Memory", "en-US")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_speech_records() {
+        let engine = MemorySpeech::new();
+        engine.speak("Hello", &VoiceConfig::default()).unwrap();
+        engine
+            .speak_immediate("Goodbye", &VoiceConfig::default().volume(0.5))
+            .unwrap();
+
+        assert_eq!(engine.spoken(), vec!["Hello", "Goodbye"]);
+        assert_eq!(engine.utterances()[1].1.volume, 0.5);
+        assert_eq!(engine.stop_count(), 1);
+        assert_eq!(engine.last_spoken(), Some("Goodbye".to_string()));
+
+        engine.clear();
+        assert!(engine.spoken().is_empty());
+    }
+}
@@ -3,6 +3,10 @@
 //! This module provides platform-agnostic traits for text-to-speech,
 //! allowing different implementations for iOS, Android, desktop, and web.
 
+mod memory;
+mod prompt;
 mod r#trait;
 
+pub use memory::MemorySpeech;
+pub use prompt::ScanPrompter;
 pub use r#trait::{SpeechEngine, Voice, VoiceConfig, VoiceGender, VoiceQuality};
@@ -0,0 +1,157 @@
+//! Spoken prompts for auditory scanning.
+//!
+//! Users who can't see the scan highlight hear it instead: each highlighted
+//! row, column, block or cell is announced quietly in a prompt voice, and
+//! the final selection is spoken in the normal output voice.
+
+use crate::error::SpeechError;
+use crate::input::{ScanAnnouncement, ScanLevel};
+use crate::obf::ObfBoard;
+
+use super::r#trait::{SpeechEngine, VoiceConfig};
+
+/// Speaks scan highlights in a dedicated prompt voice.
+///
+/// Cells are announced by their label. Rows, columns and blocks are
+/// announced by the label of their first visible button, falling back to
+/// "Row 2", "Column 3" or "Group 1" when the area has no labeled buttons.
+///
+/// # Example
+///
+/// ```rust
+/// use lovewords_core::input::{ScanMode, Scanner};
+/// use lovewords_core::speech::{MemorySpeech, ScanPrompter};
+/// use lovewords_core::{ObfBoard, ObfButton, VoiceConfig};
+///
+/// let mut board = ObfBoard::new("home", 1, 2);
+/// board.add_button(ObfButton::speak("hi", "Hello"));
+/// board.place_button_at("hi", 0, 0);
+///
+/// let mut scanner = Scanner::new(1, 2).with_mode(ScanMode::Linear);
+/// scanner.start();
+///
+/// let engine = MemorySpeech::new();
+/// let prompter = ScanPrompter::new(VoiceConfig::default().volume(0.5));
+/// let announcement = scanner.announcement().unwrap();
+/// prompter.announce(&engine, &announcement, &board).unwrap();
+///
+/// assert_eq!(engine.spoken(), vec!["Hello"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScanPrompter {
+    voice: VoiceConfig,
+}
+
+impl ScanPrompter {
+    /// Create a prompter speaking in the given voice.
+    pub fn new(voice: VoiceConfig) -> Self {
+        Self { voice }
+    }
+
+    /// Get the prompt voice.
+    pub fn voice(&self) -> &VoiceConfig {
+        &self.voice
+    }
+
+    /// Get the text to speak for a highlight, if any.
+    ///
+    /// Empty cells have nothing to announce.
+    pub fn prompt_text(&self, announcement: &ScanAnnouncement, board: &ObfBoard) -> Option<String> {
+        let area = announcement.area;
+        let label = (area.row..area.row + area.rows)
+            .flat_map(|row| (area.col..area.col + area.cols).map(move |col| (row, col)))
+            .filter_map(|(row, col)| board.button_at(row, col))
+            .filter(|button| !button.hidden && !button.label.is_empty())
+            .map(|button| button.label.clone())
+            .next();
+
+        match announcement.level {
+            ScanLevel::Cell => label,
+            ScanLevel::Row => label.or_else(|| Some(format!("Row {}", announcement.index + 1))),
+            ScanLevel::Column => {
+                label.or_else(|| Some(format!("Column {}", announcement.index + 1)))
+            }
+            ScanLevel::Block => label.or_else(|| Some(format!("Group {}", announcement.index + 1))),
+        }
+    }
+
+    /// Speak a highlight, interrupting the previous prompt.
+    ///
+    /// Returns the text spoken, if any.
+    pub fn announce(
+        &self,
+        engine: &dyn SpeechEngine,
+        announcement: &ScanAnnouncement,
+        board: &ObfBoard,
+    ) -> Result<Option<String>, SpeechError> {
+        let Some(text) = self.prompt_text(announcement, board) else {
+            return Ok(None);
+        };
+        engine.speak_immediate(&text, &self.voice)?;
+        Ok(Some(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{ScanMode, Scanner};
+    use crate::obf::ObfButton;
+    use crate::speech::MemorySpeech;
+
+    fn board() -> ObfBoard {
+        let mut board = ObfBoard::new("home", 2, 2);
+        board.add_button(ObfButton::speak("hi", "Hello"));
+        board.add_button(ObfButton::speak("bye", "Goodbye"));
+        board.place_button_at("hi", 0, 1);
+        board.place_button_at("bye", 1, 0);
+        board
+    }
+
+    #[test]
+    fn test_row_and_cell_prompts() {
+        let board = board();
+        let prompter = ScanPrompter::new(VoiceConfig::default().volume(0.4));
+        let engine = MemorySpeech::new();
+        let mut scanner = Scanner::from_occupancy(vec![vec![true, true], vec![true, true]]);
+
+        scanner.start();
+        let row = scanner.announcement().unwrap();
+        assert_eq!(prompter.prompt_text(&row, &board).as_deref(), Some("Hello"));
+
+        scanner.on_switch_press();
+        let empty_cell = scanner.announcement().unwrap();
+        assert_eq!(prompter.prompt_text(&empty_cell, &board), None);
+
+        scanner.advance();
+        let cell = scanner.announcement().unwrap();
+        prompter.announce(&engine, &cell, &board).unwrap();
+
+        let (text, voice) = &engine.utterances()[0];
+        assert_eq!(text, "Hello");
+        assert_eq!(voice.volume, 0.4);
+    }
+
+    #[test]
+    fn test_fallback_prompts() {
+        let board = ObfBoard::new("empty", 2, 2);
+        let prompter = ScanPrompter::new(VoiceConfig::default());
+
+        let mut scanner = Scanner::new(2, 2).with_mode(ScanMode::ColumnRow);
+        scanner.start();
+        scanner.advance();
+        let column = scanner.announcement().unwrap();
+        assert_eq!(
+            prompter.prompt_text(&column, &board).as_deref(),
+            Some("Column 2")
+        );
+
+        let mut scanner = Scanner::new(2, 2).with_mode(ScanMode::Block);
+        scanner.start();
+        let block = scanner.announcement().unwrap();
+        assert_eq!(
+            prompter.prompt_text(&block, &board).as_deref(),
+            Some("Group 1")
+        );
+    }
+}
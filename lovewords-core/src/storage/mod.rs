@@ -10,7 +10,12 @@ use crate::error::StorageError;
 use crate::obf::ObfBoard;
 
 pub use memory::MemoryStorage;
-pub use profile::{Profile, ProfileId, ProfileSettings};
+pub(crate) use profile::duration_millis;
+pub use profile::{
+    AccessibilitySettings, DisplaySettings, InputSettings, KeyGuardSettings, MorseMode,
+    MorseSettings, Profile, ProfileId, ProfileSettings, ScanRateMode, ScanRateSettings, SpeakMode,
+    SwitchAction, TextSize, Theme, UsageStats, VoiceSettings,
+};
 
 /// Unique identifier for a board.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
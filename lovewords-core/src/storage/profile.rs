@@ -4,9 +4,11 @@
 //! scanning configuration, and accessibility options.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 
-use crate::input::ScanMode;
+use crate::input::{BindingMap, ScanMode, ScanOrder, ScanTechnique};
+use crate::obf::ObfBoard;
 use crate::speech::{VoiceConfig, VoiceGender, VoiceQuality};
 
 /// Unique identifier for a profile.
@@ -52,6 +54,10 @@ pub struct Profile {
     /// Personalized settings.
     pub settings: ProfileSettings,
 
+    /// How often each button has been selected.
+    #[serde(default)]
+    pub usage: UsageStats,
+
     /// Profile creation timestamp.
     pub created_at: chrono::DateTime<chrono::Utc>,
 
@@ -68,6 +74,7 @@ impl Profile {
             name: name.into(),
             home_board_id: None,
             settings: ProfileSettings::default(),
+            usage: UsageStats::default(),
             created_at: now,
             updated_at: now,
         }
@@ -81,6 +88,7 @@ impl Profile {
             name: name.into(),
             home_board_id: None,
             settings: ProfileSettings::default(),
+            usage: UsageStats::default(),
             created_at: now,
             updated_at: now,
         }
@@ -98,6 +106,62 @@ impl Profile {
     }
 }
 
+/// Selection counts per button ID.
+///
+/// Used to visit frequently used buttons first when scanning.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageStats {
+    counts: BTreeMap<String, u32>,
+}
+
+impl UsageStats {
+    /// Create empty usage statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one selection of a button.
+    pub fn record(&mut self, button_id: &str) {
+        let count = self.counts.entry(button_id.to_string()).or_insert(0);
+        *count = count.saturating_add(1);
+    }
+
+    /// Get how often a button has been selected.
+    pub fn count(&self, button_id: &str) -> u32 {
+        self.counts.get(button_id).copied().unwrap_or(0)
+    }
+
+    /// Check if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Forget all recorded selections.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+
+    /// Get `[row][col]` scan weights for a board.
+    ///
+    /// Each cell weighs its button's usage count plus its
+    /// `ext_lovewords_priority`, so one point of priority is worth one use.
+    pub fn scan_weights(&self, board: &ObfBoard) -> Vec<Vec<i64>> {
+        (0..board.grid.rows)
+            .map(|row| {
+                (0..board.grid.columns)
+                    .map(|col| {
+                        board.button_at(row, col).map_or(0, |button| {
+                            i64::from(self.count(&button.id))
+                                + i64::from(button.extensions.priority.unwrap_or(0))
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
 /// User-specific settings.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProfileSettings {
@@ -137,6 +201,10 @@ pub struct VoiceSettings {
 
     /// Preferred quality.
     pub quality: VoiceQuality,
+
+    /// Whether selecting a button speaks it or only builds a sentence.
+    #[serde(default)]
+    pub speak_mode: SpeakMode,
 }
 
 impl Default for VoiceSettings {
@@ -149,11 +217,31 @@ impl Default for VoiceSettings {
             locale: None,
             gender: None,
             quality: VoiceQuality::Default,
+            speak_mode: SpeakMode::default(),
         }
     }
 }
 
+/// When button selections are spoken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpeakMode {
+    /// Speak each button as it is selected, and add it to the message bar.
+    #[default]
+    SpeakOnSelect,
+    /// Only add buttons to the message bar; speak the whole sentence on request.
+    BuildSentence,
+}
+
 impl VoiceSettings {
+    /// Quieter, faster settings suited to auditory scanning prompts.
+    pub fn prompt() -> Self {
+        Self {
+            rate: 1.25,
+            volume: 0.6,
+            ..Self::default()
+        }
+    }
+
     /// Convert to a VoiceConfig for speech synthesis.
     pub fn to_voice_config(&self) -> VoiceConfig {
         VoiceConfig {
@@ -181,6 +269,14 @@ pub struct AccessibilitySettings {
     #[serde(with = "duration_millis")]
     pub scan_interval: Duration,
 
+    /// Order in which scanning visits items.
+    #[serde(default)]
+    pub scan_order: ScanOrder,
+
+    /// Tuning of `scan_interval` from the user's reaction times.
+    #[serde(default)]
+    pub scan_rate: ScanRateSettings,
+
     /// Enable dwell selection (hover to select).
     pub dwell_enabled: bool,
 
@@ -188,6 +284,15 @@ pub struct AccessibilitySettings {
     #[serde(with = "duration_millis")]
     pub dwell_time: Duration,
 
+    /// Time before the same cell can be dwell-selected again.
+    #[serde(default = "default_dwell_cooldown", with = "duration_millis")]
+    pub dwell_cooldown: Duration,
+
+    /// How far (in logical pixels) the pointer may drift outside the
+    /// hovered cell without restarting the dwell.
+    #[serde(default = "default_dwell_tolerance")]
+    pub dwell_tolerance: f32,
+
     /// Enable visual feedback for focus.
     pub visual_feedback: bool,
 
@@ -199,6 +304,14 @@ pub struct AccessibilitySettings {
 
     /// Minimum touch target size in logical pixels.
     pub min_touch_target: u32,
+
+    /// Speak each scan highlight as a prompt (auditory scanning).
+    #[serde(default)]
+    pub auditory_scanning: bool,
+
+    /// Voice for auditory scanning prompts; `speak_mode` is ignored.
+    #[serde(default = "VoiceSettings::prompt")]
+    pub prompt_voice: VoiceSettings,
 }
 
 impl Default for AccessibilitySettings {
@@ -207,16 +320,71 @@ impl Default for AccessibilitySettings {
             switch_scanning_enabled: false,
             scan_mode: ScanMode::RowColumn,
             scan_interval: Duration::from_millis(1000),
+            scan_order: ScanOrder::default(),
+            scan_rate: ScanRateSettings::default(),
             dwell_enabled: false,
             dwell_time: Duration::from_millis(1000),
+            dwell_cooldown: default_dwell_cooldown(),
+            dwell_tolerance: default_dwell_tolerance(),
             visual_feedback: true,
             audio_feedback: true,
             haptic_feedback: true,
             min_touch_target: 44, // iOS/Android recommended minimum
+            auditory_scanning: false,
+            prompt_voice: VoiceSettings::prompt(),
         }
     }
 }
 
+fn default_dwell_cooldown() -> Duration {
+    Duration::from_millis(1000)
+}
+
+fn default_dwell_tolerance() -> f32 {
+    12.0
+}
+
+/// Adaptive scan rate settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanRateSettings {
+    /// Whether interval changes are applied, only suggested, or not made.
+    pub mode: ScanRateMode,
+
+    /// Shortest interval the tuner may choose.
+    #[serde(with = "duration_millis")]
+    pub min_interval: Duration,
+
+    /// Longest interval the tuner may choose.
+    #[serde(with = "duration_millis")]
+    pub max_interval: Duration,
+
+    /// Number of selections to measure before changing the interval.
+    pub samples: usize,
+}
+
+impl Default for ScanRateSettings {
+    fn default() -> Self {
+        Self {
+            mode: ScanRateMode::default(),
+            min_interval: Duration::from_millis(500),
+            max_interval: Duration::from_millis(4000),
+            samples: 10,
+        }
+    }
+}
+
+/// What the scan rate tuner does with what it learns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScanRateMode {
+    /// Keep the configured interval.
+    #[default]
+    Off,
+    /// Suggest a new interval for a caregiver to accept.
+    Suggest,
+    /// Change the interval automatically.
+    Automatic,
+}
+
 /// Display settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisplaySettings {
@@ -304,6 +472,22 @@ pub struct InputSettings {
 
     /// Secondary switch action (for two-switch users).
     pub secondary_switch_action: SwitchAction,
+
+    /// How switches move the scan highlight and make selections.
+    #[serde(default)]
+    pub scan_technique: ScanTechnique,
+
+    /// Filtering of accidental touches and switch presses.
+    #[serde(default)]
+    pub key_guard: KeyGuardSettings,
+
+    /// Physical keys and buttons, and the switches they act as.
+    #[serde(default)]
+    pub bindings: BindingMap,
+
+    /// Morse code input with switches.
+    #[serde(default)]
+    pub morse: MorseSettings,
 }
 
 impl Default for InputSettings {
@@ -314,10 +498,97 @@ impl Default for InputSettings {
             swipe_navigation: true,
             primary_switch_action: SwitchAction::Select,
             secondary_switch_action: SwitchAction::Back,
+            scan_technique: ScanTechnique::default(),
+            key_guard: KeyGuardSettings::default(),
+            bindings: BindingMap::default(),
+            morse: MorseSettings::default(),
         }
     }
 }
 
+impl InputSettings {
+    /// Get the action for a switch.
+    ///
+    /// Actions in [`bindings`](Self::bindings) take precedence; otherwise
+    /// switch 0 is primary and switch 1 is secondary.
+    pub fn switch_action(&self, switch_id: u8) -> Option<SwitchAction> {
+        self.bindings.switch_action(switch_id).or(match switch_id {
+            0 => Some(self.primary_switch_action),
+            1 => Some(self.secondary_switch_action),
+            _ => None,
+        })
+    }
+
+    /// Get the actions of every configured switch, by switch id.
+    pub fn switch_actions(&self) -> BTreeMap<u8, SwitchAction> {
+        let mut actions = BTreeMap::from([
+            (0, self.primary_switch_action),
+            (1, self.secondary_switch_action),
+        ]);
+        actions.extend(self.bindings.switch_actions.clone());
+        actions
+    }
+}
+
+/// Rules for ignoring accidental activations (tremor, spasticity).
+///
+/// All rules are off by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyGuardSettings {
+    /// Time a touch or switch must be held before it counts.
+    #[serde(with = "duration_millis")]
+    pub acceptance_time: Duration,
+
+    /// Select when the touch or switch is released rather than pressed.
+    pub activate_on_release: bool,
+
+    /// Time after a selection during which further selections are ignored.
+    #[serde(with = "duration_millis")]
+    pub ignore_repeat: Duration,
+
+    /// Select the cell first touched, ignoring other fingers and sliding.
+    pub first_touch_only: bool,
+}
+
+/// Morse code input settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MorseSettings {
+    /// Use switches for Morse code instead of scanning.
+    pub enabled: bool,
+
+    /// Whether dots and dashes come from one switch or two.
+    pub mode: MorseMode,
+
+    /// Length of a dot; a dash is three units and a letter ends after a
+    /// three-unit pause.
+    #[serde(with = "duration_millis")]
+    pub unit: Duration,
+
+    /// Follow the user's actual speed instead of a fixed unit.
+    pub adaptive: bool,
+}
+
+impl Default for MorseSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: MorseMode::default(),
+            unit: Duration::from_millis(200),
+            adaptive: true,
+        }
+    }
+}
+
+/// How Morse dots and dashes are entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MorseMode {
+    /// One switch: short presses are dots, long presses are dashes.
+    #[default]
+    SingleSwitch,
+    /// Two switches: the primary switch is a dot, the secondary a dash.
+    TwoSwitch,
+}
+
 /// Switch action options.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SwitchAction {
@@ -334,7 +605,7 @@ pub enum SwitchAction {
 }
 
 /// Serialization helper for Duration as milliseconds.
-mod duration_millis {
+pub(crate) mod duration_millis {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use std::time::Duration;
 
@@ -394,6 +665,35 @@ mod tests {
         assert_eq!(profile.name, parsed.name);
     }
 
+    #[test]
+    fn test_usage_scan_weights() {
+        use crate::obf::{ObfButton, ObfExtensions};
+
+        let mut board = ObfBoard::new("home", 1, 3);
+        board.add_button(ObfButton::speak("hi", "Hello"));
+        board.add_button(ObfButton {
+            extensions: ObfExtensions {
+                priority: Some(5),
+                ..Default::default()
+            },
+            ..ObfButton::speak("love", "I love you")
+        });
+        board.place_button_at("hi", 0, 0);
+        board.place_button_at("love", 0, 1);
+
+        let mut usage = UsageStats::new();
+        usage.record("hi");
+        usage.record("hi");
+        usage.record("love");
+
+        assert_eq!(usage.count("hi"), 2);
+        assert_eq!(usage.scan_weights(&board), vec![vec![2, 6, 0]]);
+
+        // Serialized as a plain map
+        let json = serde_json::to_value(&usage).unwrap();
+        assert_eq!(json, serde_json::json!({"hi": 2, "love": 1}));
+    }
+
     #[test]
     fn test_text_size_scale() {
         assert_eq!(TextSize::Small.scale(), 0.85);
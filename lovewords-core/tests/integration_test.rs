@@ -3,6 +3,7 @@
 //! These tests verify the complete workflow of loading boards,
 //! navigating, and interacting with cells.
 
+use lovewords_core::input::{DwellConfig, GazeConfig, GazeFilter, GazeSample, GridGeometry, Rect};
 use lovewords_core::storage::BoardId;
 use lovewords_core::{
     Board, BoardNavigator, CellAction, InputEvent, MemoryStorage, ObfBoard, ObfButton, Scanner,
@@ -172,3 +173,36 @@ fn test_find_cells_by_warmth() {
         assert!(cell.extensions().intimacy_level.is_some());
     }
 }
+
+/// Test that the bundled starter board passes structural validation.
+#[test]
+fn test_starter_board_is_valid() {
+    let json = include_str!("../boards/love-and-affection.json");
+    let obf: ObfBoard = serde_json::from_str(json).unwrap();
+
+    let diagnostics = obf.validate();
+    assert!(
+        diagnostics.is_empty(),
+        "Unexpected diagnostics: {:?}",
+        diagnostics
+    );
+}
+
+/// Test replaying a recorded eye-gaze trace.
+#[test]
+fn test_gaze_trace_replay() {
+    let csv = include_str!("fixtures/gaze_trace.csv");
+    let samples = GazeSample::parse_csv(csv).expect("Failed to parse gaze trace");
+    assert!(samples.iter().any(|s| !s.valid));
+
+    let geometry = GridGeometry::new(Rect::new(0.0, 0.0, 800.0, 600.0), 3, 4);
+    let mut gaze = GazeFilter::new(geometry, GazeConfig::default(), DwellConfig::default());
+    let selected: Vec<_> = gaze
+        .replay(&samples)
+        .iter()
+        .filter_map(InputEvent::cell_position)
+        .collect();
+
+    // The glance across the board and the blink don't select anything
+    assert_eq!(selected, vec![(0, 0), (1, 2)]);
+}